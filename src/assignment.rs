@@ -0,0 +1,85 @@
+use std::collections::btree_map::{self, BTreeMap};
+use std::fmt;
+
+/// Values given to variables by name, possibly leaving some unassigned.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Assignment(BTreeMap<String, bool>);
+
+impl Assignment {
+    pub fn new() -> Self {
+        Assignment(BTreeMap::new())
+    }
+
+    /// The assignment of `values[i]` to `variables[i]`.
+    pub fn from_values(variables: &[String], values: &[bool]) -> Self {
+        variables
+            .iter()
+            .cloned()
+            .zip(values.iter().copied())
+            .collect()
+    }
+
+    /// Sets the value of `variable`, returning its previous value.
+    pub fn insert(&mut self, variable: impl Into<String>, value: bool) -> Option<bool> {
+        self.0.insert(variable.into(), value)
+    }
+
+    pub fn remove(&mut self, variable: &str) -> Option<bool> {
+        self.0.remove(variable)
+    }
+
+    pub fn get(&self, variable: &str) -> Option<bool> {
+        self.0.get(variable).copied()
+    }
+
+    pub fn contains(&self, variable: &str) -> bool {
+        self.0.contains_key(variable)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The assigned variables and their values, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.0.iter().map(|(v, b)| (v.as_str(), *b))
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, bool)> for Assignment {
+    fn from_iter<I: IntoIterator<Item = (S, bool)>>(iter: I) -> Self {
+        Assignment(iter.into_iter().map(|(v, b)| (v.into(), b)).collect())
+    }
+}
+
+impl<S: Into<String>> Extend<(S, bool)> for Assignment {
+    fn extend<I: IntoIterator<Item = (S, bool)>>(&mut self, iter: I) {
+        self.0.extend(iter.into_iter().map(|(v, b)| (v.into(), b)))
+    }
+}
+
+impl IntoIterator for Assignment {
+    type Item = (String, bool);
+    type IntoIter = btree_map::IntoIter<String, bool>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl fmt::Display for Assignment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{")?;
+        for (i, (v, b)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", v, if b { 1 } else { 0 })?;
+        }
+        write!(f, "}}")
+    }
+}
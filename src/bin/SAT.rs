@@ -1,7 +1,7 @@
 use rsb::parser::Parser;
 
 fn sat(formula: &str) -> bool {
-    let mut parser = Parser::new();
+    let parser = Parser::new();
     parser.is_satisfiable(formula)
 }
 
@@ -11,8 +11,14 @@ fn main() {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod sat {
     use super::*;
+    use rsb::assignment::Assignment;
+    use rsb::cnf::Cnf;
+    use rsb::dpll;
+    use rsb::formula::Formula;
+    use rsb::random::Random;
 
     #[test]
     fn assert_equal() {
@@ -41,4 +47,152 @@ mod sat {
         assert_eq!(sat("AAA^^"), true);
         assert_eq!(sat("ABCDE^^^^"), true);
     }
+
+    #[test]
+    fn constants() {
+        assert_eq!(sat("1"), true);
+        assert_eq!(sat("0"), false);
+        assert_eq!(sat("A1&"), true);
+        assert_eq!(sat("A0&"), false);
+        assert_eq!(sat("A1&B|"), true);
+        assert_eq!(sat("AA!^0>"), false);
+    }
+
+    // A random formula over `len` variables
+    fn random_formula(len: usize, size: usize, random: &mut Random) -> String {
+        let mut next = || random.next_usize();
+        let mut rpn = String::new();
+        let mut depth = 0;
+        while depth != 1 || rpn.len() < size {
+            match next() % 8 {
+                0 if depth >= 1 => rpn.push('!'),
+                1..=3 if depth >= 2 || rpn.len() >= size => {
+                    rpn.push(['&', '|', '^', '>', '=', '↑', '↓', '<'][next() % 8]);
+                    depth -= 1;
+                }
+                _ if rpn.len() < size => {
+                    rpn.push((b'A' + (next() % len) as u8) as char);
+                    depth += 1;
+                }
+                _ => (),
+            }
+        }
+        rpn
+    }
+
+    #[test]
+    fn random_formulas() {
+        let parser = Parser::new();
+        let mut random = Random::new(0x2545f4914f6cdd1d);
+        for _ in 0..500 {
+            let formula = random_formula(6, 12, &mut random);
+            let table = parser.truth_table_from(&formula).unwrap();
+            assert_eq!(sat(&formula), table.count_ones() > 0, "{}", formula);
+        }
+    }
+
+    #[test]
+    fn many_variables() {
+        // x0 -> x1 -> ... -> x79, with x0 and not x79
+        let chain = (1..80)
+            .map(|i| format!("x{} x{} >", i - 1, i))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let implications = format!("{} {}", chain, "& ".repeat(78));
+        assert_eq!(sat(&format!("{} x0 & x79 &", implications)), true);
+        assert_eq!(sat(&format!("{} x0 & x79 ! &", implications)), false);
+
+        // 16 groups of 4 options with exactly one option per group, the
+        // first option of a group excluding the second option of the next
+        let groups = (0..16)
+            .map(|g| format!("g{g}_0 g{g}_1 g{g}_2 g{g}_3 exo/4"))
+            .chain((0..15).map(|g| format!("g{}_0 g{}_1 ! >", g, g + 1)))
+            .collect::<Vec<_>>();
+        let config = format!("{} {}", groups.join(" "), "& ".repeat(groups.len() - 1));
+        assert_eq!(sat(&config), true);
+        assert_eq!(sat(&format!("{} g0_3 ! g0_2 ! g0_1 ! & & &", config)), true);
+
+        // 7 pigeons in 6 holes
+        let pigeons = (0..7)
+            .map(|p| {
+                let holes = (0..6).map(|h| format!("p{p}_{h}")).collect::<Vec<_>>();
+                format!("{} {}", holes.join(" "), "| ".repeat(5))
+            })
+            .chain((0..6).map(|h| {
+                let pigeons = (0..7).map(|p| format!("p{p}_{h}")).collect::<Vec<_>>();
+                format!("{} amo/7", pigeons.join(" "))
+            }))
+            .collect::<Vec<_>>();
+        let formula = format!("{} {}", pigeons.join(" "), "& ".repeat(12));
+        assert_eq!(sat(&formula), false);
+
+        // The cardinality connectives are encoded without expanding them
+        let operands = (0..28)
+            .map(|i| format!("x{}", i))
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(sat(&format!("{} maj/28", operands)), true);
+        assert_eq!(
+            sat(&format!(
+                "{} maj/28 {} atleast15/28 ! &",
+                operands, operands
+            )),
+            false
+        );
+    }
+
+    #[test]
+    fn dpll() {
+        let parser = Parser::new();
+        let cnf = parser.evaluate_cnf("AB|C&A!B!|&D^").unwrap();
+        let model = dpll::solve(&cnf).unwrap();
+        assert!(cnf.evaluate(&model));
+        assert_eq!(dpll::solve(&parser.evaluate_cnf("AB^AB=&").unwrap()), None);
+        assert_eq!(dpll::solve(&Cnf::constant(true)), Some(vec![]));
+        assert_eq!(dpll::solve(&Cnf::constant(false)), None);
+    }
+
+    #[test]
+    fn models() {
+        let parser = Parser::new();
+        let mut random = Random::new(0x9e3779b97f4a7c15);
+        for _ in 0..300 {
+            let formula = random_formula(6, 12, &mut random);
+            let model = parser.find_model(&formula);
+            assert_eq!(model.is_some(), sat(&formula));
+            if let Some(model) = model {
+                let formula = Formula::parse_rpn(&formula).unwrap();
+                assert_eq!(model.len(), formula.variables().len());
+                assert_eq!(formula.eval(&model).unwrap(), true);
+            }
+        }
+
+        let model = parser.find_model("AB&C!&").unwrap();
+        assert_eq!(model.to_string(), "{A: 1, B: 1, C: 0}");
+        // B is folded away with the constant but still gets a value
+        let model = parser.find_model("AB1|&").unwrap();
+        assert_eq!(model.to_string(), "{A: 1, B: 0}");
+        assert_eq!(parser.find_model("1"), Some(Assignment::new()));
+        assert_eq!(parser.find_model("AA!&"), None);
+        assert_eq!(parser.find_model("A&"), None);
+
+        // 16 groups of 4 options, exactly one per group, the first option of
+        // a group requiring the last option of the next
+        let groups = (0..16)
+            .map(|g| format!("g{g}_0 g{g}_1 g{g}_2 g{g}_3 exo/4"))
+            .chain((0..15).map(|g| format!("g{}_0 g{}_3 >", g, g + 1)))
+            .collect::<Vec<_>>();
+        let config = format!(
+            "{} {} g0_0 &",
+            groups.join(" "),
+            "& ".repeat(groups.len() - 1)
+        );
+        let model = parser.find_model(&config).unwrap();
+        assert_eq!(model.len(), 64);
+        assert_eq!(model.get("g0_0"), Some(true));
+        assert_eq!(model.get("g1_3"), Some(true));
+        assert_eq!(model.get("g1_0"), Some(false));
+        let formula = Formula::parse_rpn(&config).unwrap();
+        assert_eq!(formula.eval(&model).unwrap(), true);
+    }
 }
@@ -1,11 +1,8 @@
 use rsb::parser::Parser;
 
 fn eval_formula(formula: &str) -> bool {
-    let mut parser = Parser::new();
-    match parser.evaluate(formula) {
-        Ok(_) => parser.result.unwrap(),
-        _ => false,
-    }
+    let parser = Parser::new();
+    parser.evaluate(formula).unwrap_or(false)
 }
 
 #[allow(dead_code)]
@@ -14,8 +11,14 @@ fn main() {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod boolean_evaluation {
     use super::*;
+    use rsb::assignment::Assignment;
+    use rsb::error::{ParseErrorKind, UnassignedVariables};
+    use rsb::formula::Formula;
+    use std::sync::Arc;
+    use std::thread;
 
     #[test]
     fn assert_equal() {
@@ -59,4 +62,121 @@ mod boolean_evaluation {
         assert_eq!(eval_formula("01&1&1&"), false);
         assert_eq!(eval_formula("0111&&&"), false);
     }
+
+    #[test]
+    fn formula_tree() {
+        assert_eq!(
+            Formula::parse_rpn("AB&C!|").unwrap(),
+            Formula::or(
+                Formula::and(Formula::var("A"), Formula::var("B")),
+                !Formula::var("C")
+            )
+        );
+        for formula in ["10&", "AB>!C=", "ABC^^", "A!B&!C|!D!&!E!&!A>!!!F=G!&"] {
+            assert_eq!(Formula::parse_rpn(formula).unwrap().to_rpn(), formula);
+        }
+        assert!(Formula::parse_rpn("A&").is_err());
+        assert!(Formula::parse_rpn("AB?").is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = Formula::parse_rpn("AB?&").unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedCharacter);
+        assert_eq!((error.offset, error.found, error.depth), (2, Some('?'), 2));
+        assert_eq!(error.render(), "AB?&\n  ^");
+
+        let error = Formula::parse_rpn("A&").unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::MissingOperand);
+        assert_eq!((error.offset, error.found, error.depth), (1, Some('&'), 1));
+
+        let error = Formula::parse_rpn("AB").unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::TrailingOperands);
+        assert_eq!((error.offset, error.found, error.depth), (2, None, 2));
+        assert_eq!(
+            error.to_string(),
+            "2 operands left without an operator at offset 2 (depth 2), expected an operator\nAB\n  ^"
+        );
+
+        let error = Formula::parse_rpn("").unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedEnd);
+
+        assert_eq!(eval_formula("101&"), false);
+        assert_eq!(eval_formula("1 0&"), false);
+    }
+
+    #[test]
+    fn connectives() {
+        assert_eq!(eval_formula("11↑"), false);
+        assert_eq!(eval_formula("10↑"), true);
+        assert_eq!(eval_formula("00↓"), true);
+        assert_eq!(eval_formula("01↓"), false);
+        assert_eq!(eval_formula("01<"), false);
+        assert_eq!(eval_formula("10<"), true);
+        assert_eq!(eval_formula("1 0 1 amo/3"), false);
+        assert_eq!(eval_formula("1 0 0 amo/3"), true);
+        assert_eq!(eval_formula("0 0 0 exo/3"), false);
+        assert_eq!(eval_formula("0 1 0 exo/3"), true);
+        assert_eq!(eval_formula("1 1 0 maj/3"), true);
+        assert_eq!(eval_formula("1 0 0 1 maj/4"), false);
+        assert_eq!(eval_formula("1 0 0 1 atleast2/4"), true);
+        assert_eq!(eval_formula("1 0 0 0 atleast2/4 !"), true);
+        assert_eq!(eval_formula("1 1 amo/3"), false);
+        assert_eq!(eval_formula("1 1 1 nope/3"), false);
+    }
+
+    #[test]
+    fn assignment() {
+        let formula = Formula::parse_rpn("AB&C|").unwrap();
+        let assignment = Assignment::from_iter([("A", true), ("B", false), ("C", true)]);
+        assert_eq!(formula.eval(&assignment).unwrap(), true);
+        let assignment = Assignment::from_iter([("A", true), ("B", false), ("C", false)]);
+        assert_eq!(formula.eval(&assignment).unwrap(), false);
+        assert_eq!(assignment.to_string(), "{A: 1, B: 0, C: 0}");
+
+        let mut assignment = Assignment::new();
+        assignment.insert("C", true);
+        assert_eq!(formula.eval(&assignment).unwrap(), true);
+        assignment.insert("C", false);
+        assert_eq!(formula.assign(&assignment).to_rpn(), "AB&");
+        let error = formula.eval(&assignment).unwrap_err();
+        assert_eq!(
+            error,
+            UnassignedVariables {
+                residual: Formula::parse_rpn("AB&").unwrap()
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Unassigned variables A, B, residual formula 'A & B'"
+        );
+
+        assignment.insert("A", true);
+        assert_eq!(formula.assign(&assignment), Formula::var("B"));
+        assignment.insert("B", true);
+        assert_eq!(formula.eval(&assignment).unwrap(), true);
+        assert_eq!(formula.assign(&Assignment::new()), formula);
+
+        let formula = Formula::parse_rpn("x1 x2 x3 exo/3 y ^").unwrap();
+        let assignment = Assignment::from_iter([("x1", true), ("y", true)]);
+        assert_eq!(formula.assign(&assignment).to_rpn(), "x2 x3 atleast1/2");
+    }
+
+    #[test]
+    fn shared_parser() {
+        let parser = Arc::new(Parser::new());
+        let workers = (0..8)
+            .map(|i| {
+                let parser = Arc::clone(&parser);
+                thread::spawn(move || {
+                    let formula = if i % 2 == 0 { "10|1&" } else { "1011||!=" };
+                    (0..100).all(|_| parser.evaluate(formula).unwrap() == (i % 2 == 0))
+                })
+            })
+            .collect::<Vec<_>>();
+        for worker in workers {
+            assert!(worker.join().unwrap());
+        }
+        assert!(parser.evaluate("A1&").is_err());
+    }
 }
@@ -1,9 +1,9 @@
 use rsb::parser::Parser;
 
 fn conjunctive_normal_form(formula: &str) -> String {
-    let mut parser = Parser::new();
+    let parser = Parser::new();
     if let Ok(cnf) = parser.evaluate_cnf(formula) {
-        return cnf;
+        return cnf.to_rpn();
     }
     String::from(formula)
 }
@@ -16,12 +16,20 @@ fn main() {
 #[cfg(test)]
 mod conjunctive_normal_form {
     use super::*;
+    use rsb::cnf::{Clause, Cnf, Lit};
+    use rsb::error::TooManyClauses;
+    use rsb::espresso;
+    use rsb::formula::Formula;
+    use rsb::parser::Minimiser;
+    use rsb::petrick::{essential_implicants, minimum_covers, Cost};
+    use rsb::quinemccluskey::{prime_implicants, Implicant};
+    use rsb::truthtable::TruthTable;
 
     fn assert_equal_truthtable(formula: &str) {
-        let mut parser = Parser::new();
-        if let Ok(table1) = parser.truth_table_from(&formula) {
-            if let Ok(cnf) = parser.evaluate_cnf(&formula) {
-                if let Ok(table2) = parser.truth_table_from(&cnf) {
+        let parser = Parser::new();
+        if let Ok(table1) = parser.truth_table_from(formula) {
+            if let Ok(cnf) = parser.evaluate_cnf(formula) {
+                if let Ok(table2) = parser.truth_table_from(&cnf.to_rpn()) {
                     assert_eq!(table1, table2);
                 }
             }
@@ -68,4 +76,289 @@ mod conjunctive_normal_form {
         assert_equal_truthtable("ABC^^");
         assert_equal_truthtable("ABC>>");
     }
+
+    #[test]
+    fn constants() {
+        assert_equal_truthtable("A1&B|");
+        assert_equal_truthtable("AB0|&");
+        assert_equal_truthtable("A1^B&");
+        assert_equal_truthtable("AB0>=");
+        assert_eq!(conjunctive_normal_form("1"), "1");
+        assert_eq!(conjunctive_normal_form("10&"), "0");
+        assert_eq!(conjunctive_normal_form("A1|"), "1");
+    }
+
+    #[test]
+    fn connectives() {
+        assert_equal_truthtable("AB↑");
+        assert_equal_truthtable("AB↓C|");
+        assert_equal_truthtable("AB<C↑");
+        assert_equal_truthtable("A B C amo/3");
+        assert_equal_truthtable("A B C exo/3");
+        assert_equal_truthtable("A B C D maj/4");
+        assert_equal_truthtable("A B C D atleast2/4 !");
+        assert_equal_truthtable("A B atleast4/2 A B & |");
+        assert_eq!(
+            Parser::new()
+                .evaluate_cnf("A B atleast4/2")
+                .unwrap()
+                .to_rpn(),
+            "0"
+        );
+    }
+
+    #[test]
+    fn more_than_four_variables() {
+        assert_equal_truthtable("ABCDE||||");
+        assert_equal_truthtable("ABCDE^^^^");
+        assert_equal_truthtable("AB&CD&|E>F=");
+        assert_equal_truthtable("A B C D E F G H exo/8");
+        assert_equal_truthtable("ABC&|DE|F&G>|H^I&J|K=L&");
+        assert_eq!(conjunctive_normal_form("ABCDE&&&&"), "ABCDE&&&&");
+        assert_eq!(conjunctive_normal_form("ABCDE||||"), "ABCDE||||");
+        assert_eq!(conjunctive_normal_form("ABCDE&&&&A|"), "A");
+        assert_eq!(conjunctive_normal_form("AB&C|DE&!&"), "AC|BC|D!E!|&&");
+    }
+
+    #[test]
+    fn exact_cover() {
+        let variables = ["A", "B", "C"].map(String::from);
+        let terms = |cover: &[Implicant]| {
+            cover
+                .iter()
+                .map(|i| i.to_term(&variables).to_rpn())
+                .collect::<Vec<_>>()
+        };
+
+        // A cyclic function: no prime implicant is essential
+        let rows = [0, 1, 2, 5, 6, 7];
+        let primes = prime_implicants(3, &rows);
+        assert_eq!(primes.len(), 6);
+        assert!(essential_implicants(&primes, &rows).is_empty());
+        let covers = minimum_covers(3, &primes, &rows, Cost::Terms);
+        assert_eq!(covers.len(), 2);
+        assert_eq!(terms(&covers[0]), ["A!B!&", "BC!&", "AC&"]);
+        assert_eq!(terms(&covers[1]), ["A!C!&", "B!C&", "AB&"]);
+        assert_eq!(minimum_covers(3, &primes, &rows, Cost::Literals), covers);
+
+        let rows = [0, 1, 3];
+        let primes = prime_implicants(3, &rows);
+        let essentials = essential_implicants(&primes, &rows);
+        assert_eq!(terms(&essentials), ["A!B!&", "A!C&"]);
+        assert_eq!(minimum_covers(3, &primes, &rows, Cost::Terms), [essentials]);
+
+        // Two covers of two terms, with 3 and 4 literals
+        let implicants = [
+            Implicant {
+                value: 0b000,
+                mask: 0b011,
+            },
+            Implicant {
+                value: 0b000,
+                mask: 0b001,
+            },
+            Implicant {
+                value: 0b010,
+                mask: 0b001,
+            },
+            Implicant {
+                value: 0b000,
+                mask: 0b100,
+            },
+            Implicant {
+                value: 0b100,
+                mask: 0b000,
+            },
+        ];
+        let rows = [0, 1, 2, 3, 4];
+        let by_terms = minimum_covers(3, &implicants, &rows, Cost::Terms);
+        assert_eq!(by_terms.len(), 2);
+        assert_eq!(terms(&by_terms[0]), ["A!", "B!C!&"]);
+        assert_eq!(terms(&by_terms[1]), ["A!", "AB!C!&&"]);
+        let by_literals = minimum_covers(3, &implicants, &rows, Cost::Literals);
+        assert_eq!(by_literals, by_terms[..1]);
+        assert_eq!(Cost::Literals.of(3, &by_literals[0]), 3);
+        assert!(minimum_covers(3, &implicants[..3], &rows, Cost::Terms).is_empty());
+    }
+
+    #[test]
+    fn espresso() {
+        let parser = Parser::new();
+        let cnf = |formula: &str| {
+            parser
+                .evaluate_cnf_with(formula, Minimiser::Espresso)
+                .unwrap()
+                .to_rpn()
+        };
+        for formula in [
+            "AB&!",
+            "ABC^^",
+            "AB&CD&|E>F=",
+            "A B C D E F G H exo/8",
+            "ABC&|DE|F&G>|H^I&J|K=L&",
+        ] {
+            let table = parser.truth_table_from(formula).unwrap();
+            assert_eq!(parser.truth_table_from(&cnf(formula)).unwrap(), table);
+        }
+        assert_eq!(cnf("ABCDE&&&&"), "ABCDE&&&&");
+        assert_eq!(cnf("AB&C|DE&!&"), "AC|BC|D!E!|&&");
+        assert_eq!(cnf("A1|"), "1");
+        assert_eq!(cnf("A0&"), "0");
+    }
+
+    #[test]
+    fn espresso_many_variables() {
+        // Most of the 2^21 rows are false, far too many minterms to merge
+        // one by one as Quine-McCluskey does
+        let terms = (0..7)
+            .map(|i| format!("x{} y{} & z{} | ", i, i, i))
+            .collect::<String>();
+        let formula = Formula::parse_rpn(&(terms + &"& ".repeat(6))).unwrap();
+        let table = TruthTable::from_formula(&formula).unwrap();
+        assert_eq!(table.variables.len(), 21);
+        let cover = espresso::minimise(&table, false);
+        assert_eq!(cover.len(), 14);
+        assert!(cover.iter().all(|c| c.literal_count(21) == 2));
+        assert!(table.zeros().all(|row| cover.iter().any(|c| c.covers(row))));
+    }
+
+    #[test]
+    fn dont_cares() {
+        let parser = Parser::new();
+        let variables = |names: &str| names.chars().map(String::from).collect::<Vec<_>>();
+
+        // A BCD digit of at least 5, the codes above 9 never occurring
+        let table = TruthTable::from_sets(variables("ABCD"), 5..10, 10..16).unwrap();
+        let cnf = parser.cnf_from_table(&table, Minimiser::Exact);
+        assert_eq!(cnf.to_rpn(), "AB|ACD||&");
+        let cnf = parser.cnf_from_table(&table, Minimiser::Espresso);
+        assert_eq!(cnf.to_rpn(), "AB|ACD||&");
+        let without = TruthTable::from_sets(variables("ABCD"), 5..10, []).unwrap();
+        let cnf = parser.cnf_from_table(&without, Minimiser::Exact);
+        assert_eq!(cnf.to_rpn(), "AB|ACD||BC!|A!B!|&&&");
+
+        // The same digit with an enable input E in front
+        let table =
+            TruthTable::from_sets(variables("EABCD"), 21..26, (0..16).chain(26..32)).unwrap();
+        for strategy in [Minimiser::Exact, Minimiser::Espresso] {
+            let cnf = parser.cnf_from_table(&table, strategy);
+            assert_eq!(cnf.to_rpn(), "AB|ACD||&");
+            assert!(
+                table.is_implemented_by(&TruthTable::from_fn(variables("EABCD"), |v| {
+                    cnf.evaluate(v)
+                }))
+            );
+        }
+    }
+
+    #[test]
+    fn distribution() {
+        let parser = Parser::new();
+        let cnf = |formula: &str, max_clauses: usize| {
+            parser
+                .evaluate_cnf_distributed(formula, max_clauses)
+                .map(|cnf| cnf.to_rpn())
+        };
+        for formula in [
+            "AB&!",
+            "AB|C&",
+            "AB=C^D>",
+            "AB&CD&|EF&|",
+            "A B C D exo/4 E |",
+            "AB↑C↓D<",
+        ] {
+            assert_eq!(
+                parser.truth_table_from(formula).unwrap(),
+                parser
+                    .truth_table_from(&cnf(formula, 1000).unwrap())
+                    .unwrap()
+            );
+        }
+        assert_eq!(cnf("AB&C|", 10).unwrap(), "AC|BC|&");
+        // Subsumed and tautological clauses are dropped
+        assert_eq!(cnf("AB|A&", 10).unwrap(), "A");
+        assert_eq!(cnf("AA!|B&", 10).unwrap(), "B");
+        assert_eq!(cnf("AB&A|", 10).unwrap(), "A");
+        assert_eq!(cnf("AA!&", 10).unwrap(), "AA!&");
+        assert_eq!(cnf("AA!|", 10).unwrap(), "1");
+
+        // Beyond the Karnaugh map, a product of 8 terms has 2^8 clauses
+        let formula = "AB&CD&|EF&|GH&|IJ&|KL&|MN&|OP&|";
+        let distributed = parser.evaluate_cnf_distributed(formula, 256).unwrap();
+        assert_eq!(distributed.len(), 256);
+        assert!(distributed.iter().all(|clause| clause.len() == 8));
+        assert_eq!(
+            parser.truth_table_from(formula).unwrap(),
+            parser.truth_table_from(&distributed.to_rpn()).unwrap()
+        );
+        let error = cnf(formula, 255).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<TooManyClauses>(),
+            Some(&TooManyClauses { limit: 255 })
+        );
+
+        // Distributed without recursing, however deep the formula is
+        let deep = format!("A{}", " A |".repeat(20000));
+        assert_eq!(cnf(&deep, 10).unwrap(), "A");
+        let deep = format!("A B &{}", " A B & |".repeat(20000));
+        assert_eq!(cnf(&deep, 10).unwrap(), "AB&");
+    }
+
+    #[test]
+    fn clauses() {
+        let parser = Parser::new();
+        let cnf = parser.evaluate_cnf("AB&C|DE&!&").unwrap();
+        assert_eq!(cnf.variables, ["A", "B", "C", "D", "E"]);
+        let clauses = cnf
+            .iter()
+            .map(|clause| {
+                clause
+                    .iter()
+                    .map(|lit| (lit.var(), lit.is_positive()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            clauses,
+            [
+                vec![(0, true), (2, true)],
+                vec![(1, true), (2, true)],
+                vec![(3, false), (4, false)],
+            ]
+        );
+        assert_eq!(cnf.to_rpn(), "AC|BC|D!E!|&&");
+        assert_eq!(cnf.to_string(), "(A | C) & (B | C) & (!D | !E)");
+        assert_eq!(cnf.to_formula().to_rpn(), cnf.to_rpn());
+
+        // A single literal of a multi-character variable parses back
+        for formula in ["x12 x12 &", "x12 x12 |", "x12 ! 1 &"] {
+            let cnf = parser.evaluate_cnf(formula).unwrap();
+            let rpn = cnf.to_rpn();
+            assert_eq!(
+                Formula::parse_rpn(&rpn).unwrap(),
+                cnf.to_formula(),
+                "{}",
+                rpn
+            );
+        }
+        assert_eq!(parser.evaluate_cnf("x12 x12 &").unwrap().to_rpn(), "x12 ");
+        assert_eq!(
+            parser.evaluate_cnf("x12 ! x12 ! &").unwrap().to_rpn(),
+            "x12 !"
+        );
+
+        let lit = Lit::negative(3);
+        assert_eq!((lit.var(), lit.is_positive(), lit.code()), (3, false, 7));
+        assert_eq!(!lit, Lit::positive(3));
+        assert_eq!(Lit::from_code(6), Lit::positive(3));
+        assert!(Clause::new(vec![lit, Lit::positive(1), !lit]).is_tautology());
+
+        assert!(parser.evaluate_cnf("AA!|").unwrap().is_empty());
+        assert_eq!(parser.evaluate_cnf("AA!&").unwrap().to_rpn(), "0");
+        assert_eq!(Cnf::constant(true).to_rpn(), "1");
+        let names = Formula::parse_rpn("x1 x2 & x3 |").unwrap().variables();
+        let cnf = parser.evaluate_cnf("x1 x2 & x3 |").unwrap();
+        assert_eq!(cnf.variables, names);
+        assert_eq!(cnf.to_rpn(), "x1 x3 | x2 x3 | &");
+    }
 }
@@ -0,0 +1,173 @@
+use rsb::cnf::Cnf;
+use rsb::parser::Parser;
+
+fn dimacs(formula: &str) -> String {
+    let parser = Parser::new();
+    match parser.evaluate_cnf(formula) {
+        Ok(cnf) => cnf.to_dimacs(),
+        _ => String::from(formula),
+    }
+}
+
+fn sat_dimacs(input: &str) -> bool {
+    let parser = Parser::new();
+    match Cnf::from_dimacs(input) {
+        Ok(cnf) => parser.is_satisfiable_cnf(&cnf),
+        _ => false,
+    }
+}
+
+#[allow(dead_code)]
+fn main() {
+    sat_dimacs(&dimacs("AB&C|"));
+}
+
+#[cfg(test)]
+mod dimacs {
+    use super::*;
+    use rsb::error::DimacsErrorKind;
+    use rsb::formula::Formula;
+    use rsb::tseitin::Encoding;
+
+    // The pigeonhole problem for 3 pigeons and 2 holes, variable 2i + j
+    // putting pigeon i in hole j, as found in benchmark files
+    const PIGEONHOLE: &str = "c pigeonhole 3 2
+c
+p cnf 6 9
+1 2 0
+3 4 0
+5 6 0
+-1 -3 0 -1 -5 0
+-3 -5 0
+-2
+-4 0
+-2 -6 0
+-4 -6 0
+%
+0
+";
+
+    fn kind(input: &str) -> Option<DimacsErrorKind> {
+        Cnf::from_dimacs(input).err().map(|error| error.kind)
+    }
+
+    #[test]
+    fn export() {
+        assert_eq!(
+            dimacs("AB&C|DE&!&"),
+            "c var 1 A\nc var 2 B\nc var 3 C\nc var 4 D\nc var 5 E\n\
+             p cnf 5 3\n1 3 0\n2 3 0\n-4 -5 0\n"
+        );
+        assert_eq!(dimacs("AA!|"), "c var 1 A\np cnf 1 0\n");
+        assert_eq!(dimacs("AA!&"), "c var 1 A\np cnf 1 1\n0\n");
+
+        let formula = Formula::parse_rpn("req_valid s[3] &").unwrap();
+        let tseitin = formula.tseitin(Encoding::Polarity).cnf;
+        assert_eq!(
+            tseitin.to_dimacs(),
+            "c var 1 req_valid\nc var 2 s[3]\nc var 3 _t0\n\
+             p cnf 3 3\n-3 1 0\n-3 2 0\n3 0\n"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let parser = Parser::new();
+        for formula in ["AB&C|DE&!&", "AB^C=", "AA!&", "A B C D exo/4"] {
+            let cnf = parser.evaluate_cnf(formula).unwrap();
+            assert_eq!(Cnf::from_dimacs(&cnf.to_dimacs()), Ok(cnf));
+        }
+        let formula = Formula::parse_rpn("x1 x2 ^ x3 > y |").unwrap();
+        let tseitin = formula.tseitin(Encoding::Full).cnf;
+        assert_eq!(Cnf::from_dimacs(&tseitin.to_dimacs()), Ok(tseitin));
+    }
+
+    #[test]
+    fn import() {
+        let cnf = Cnf::from_dimacs(PIGEONHOLE).unwrap();
+        assert_eq!(cnf.variables, ["x1", "x2", "x3", "x4", "x5", "x6"]);
+        assert_eq!(cnf.len(), 9);
+        assert_eq!(cnf.to_rpn().split(' ').next(), Some("x1"));
+        assert!(!sat_dimacs(PIGEONHOLE));
+
+        let input = "c var 2 B\np cnf 3 2\n1 -2 0\n2 3\n";
+        let cnf = Cnf::from_dimacs(input).unwrap();
+        assert_eq!(cnf.variables, ["x1", "B", "x3"]);
+        assert!(sat_dimacs(input));
+
+        // A single unit clause, and its contradiction
+        assert!(sat_dimacs("p cnf 1 1\n1 0\n"));
+        assert!(sat_dimacs("p cnf 12 1\n-12 0\n"));
+        assert!(!sat_dimacs("p cnf 1 2\n1 0\n-1 0\n"));
+        let parser = Parser::new();
+        let unit = Cnf::from_dimacs("p cnf 1 1\n1 0\n").unwrap();
+        assert!(parser.is_satisfiable_cnf(&unit));
+        assert_eq!(
+            parser
+                .truth_table_from_cnf(&unit)
+                .unwrap()
+                .ones()
+                .collect::<Vec<_>>(),
+            [1]
+        );
+        let table = Parser::new().truth_table_from_cnf(&cnf).unwrap();
+        assert_eq!(table.variables, ["B", "x1", "x3"]);
+        assert_eq!(table.count_ones(), 4);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(kind("1 2 0\n"), Some(DimacsErrorKind::MissingHeader));
+        assert_eq!(
+            kind("c only comments\n"),
+            Some(DimacsErrorKind::MissingHeader)
+        );
+        assert_eq!(kind("p cnf 2\n"), Some(DimacsErrorKind::InvalidHeader));
+        assert_eq!(
+            kind("p dnf 2 1\n1 0\n"),
+            Some(DimacsErrorKind::InvalidHeader)
+        );
+        assert_eq!(
+            kind("p cnf 1 1\np cnf 1 1\n"),
+            Some(DimacsErrorKind::InvalidHeader)
+        );
+        assert_eq!(
+            kind("p cnf 2 1\n1 a 0\n"),
+            Some(DimacsErrorKind::InvalidLiteral)
+        );
+        assert_eq!(
+            kind("p cnf 2 1\n1 -3 0\n"),
+            Some(DimacsErrorKind::VariableOutOfRange)
+        );
+        assert_eq!(
+            kind("c var 3 C\np cnf 2 0\n"),
+            Some(DimacsErrorKind::VariableOutOfRange)
+        );
+        assert_eq!(
+            kind("c var 1 2A\np cnf 2 0\n"),
+            Some(DimacsErrorKind::InvalidName)
+        );
+        assert_eq!(
+            kind("c var 1 x2\np cnf 2 0\n"),
+            Some(DimacsErrorKind::InvalidName)
+        );
+        assert_eq!(
+            kind("p cnf 2 2\n1 0\n"),
+            Some(DimacsErrorKind::ClauseCountMismatch)
+        );
+
+        // A duplicate name is reported at the comment that gives it
+        let input = "c var 1 A\nc var 2 A\np cnf 3 1\n1 2 3 0\n";
+        let error = Cnf::from_dimacs(input).unwrap_err();
+        assert_eq!((error.line, error.content.as_str()), (2, "c var 2 A"));
+        let input = "c var 1 x3\nc var 2 B\np cnf 3 1\n1 2 3 0\n";
+        let error = Cnf::from_dimacs(input).unwrap_err();
+        assert_eq!((error.line, error.content.as_str()), (1, "c var 1 x3"));
+
+        let error = Cnf::from_dimacs("p cnf 2 1\n\n1 -3 0\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "variable not declared in the header at line 3: '1 -3 0'"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use rsb::parser::Parser;
+
+fn disjunctive_normal_form(formula: &str) -> String {
+    let parser = Parser::new();
+    if let Ok(dnf) = parser.evaluate_dnf(formula) {
+        return dnf;
+    }
+    String::from(formula)
+}
+
+#[allow(dead_code)]
+fn main() {
+    disjunctive_normal_form("AB&C|");
+}
+
+#[cfg(test)]
+mod disjunctive_normal_form {
+    use super::*;
+    use rsb::parser::Minimiser;
+    use rsb::truthtable::TruthTable;
+
+    fn assert_equal_truthtable(formula: &str) {
+        let parser = Parser::new();
+        let table1 = parser.truth_table_from(formula).unwrap();
+        for strategy in [Minimiser::Exact, Minimiser::Espresso] {
+            let dnf = parser.evaluate_dnf_with(formula, strategy).unwrap();
+            let table2 = parser.truth_table_from(&dnf).unwrap();
+            assert_eq!(table1.variables, table2.variables);
+            assert_eq!(table1, table2);
+        }
+    }
+
+    #[test]
+    fn assert_equal() {
+        assert_equal_truthtable("AB&!");
+        assert_equal_truthtable("AB|!");
+        assert_equal_truthtable("AB|C&");
+        assert_equal_truthtable("AB|C|D|");
+        assert_equal_truthtable("AB&C&D&");
+        assert_equal_truthtable("AB&!C!|");
+        assert_equal_truthtable("AB|!C!&");
+        assert_equal_truthtable("AB&!CD&!>!");
+    }
+
+    #[test]
+    fn eval_sheet() {
+        assert_equal_truthtable("A");
+        assert_equal_truthtable("A!");
+        assert_equal_truthtable("AB>!");
+        assert_equal_truthtable("AB=!");
+        assert_equal_truthtable("ABC||!");
+        assert_equal_truthtable("ABC|&");
+        assert_equal_truthtable("ABC&|!");
+        assert_equal_truthtable("ABC^^");
+        assert_equal_truthtable("ABC>>");
+        assert_equal_truthtable("AB↑C↓");
+        assert_equal_truthtable("A B C D maj/4");
+    }
+
+    #[test]
+    fn minimal() {
+        assert_eq!(disjunctive_normal_form("AB|C&"), "BC&AC&|");
+        assert_eq!(disjunctive_normal_form("AB&C|"), "CAB&|");
+        assert_eq!(disjunctive_normal_form("AB=!"), "A!B&AB!&|");
+        assert_eq!(disjunctive_normal_form("AB>C>"), "CAB!&|");
+        assert_eq!(disjunctive_normal_form("A B C maj/3"), "BC&AC&AB&||");
+        assert_eq!(disjunctive_normal_form("ABCDE||||!"), "A!B!C!D!E!&&&&");
+        assert_eq!(
+            disjunctive_normal_form("AB&C|DE&!&"),
+            "CD!&CE!&ABD!&&ABE!&&|||"
+        );
+    }
+
+    #[test]
+    fn more_than_four_variables() {
+        assert_equal_truthtable("ABCDE^^^^");
+        assert_equal_truthtable("AB&CD&|E>F=");
+        assert_equal_truthtable("A B C D E F G H exo/8");
+        assert_equal_truthtable("ABC&|DE|F&G>|H^I&J|K=L&");
+    }
+
+    #[test]
+    fn constants() {
+        assert_equal_truthtable("A1&B|");
+        assert_equal_truthtable("AB0>=");
+        assert_eq!(disjunctive_normal_form("1"), "1");
+        assert_eq!(disjunctive_normal_form("10&"), "0");
+        assert_eq!(disjunctive_normal_form("A0&"), "0");
+        assert_eq!(disjunctive_normal_form("A1|"), "1");
+        assert_eq!(disjunctive_normal_form("A B atleast4/2"), "0");
+        assert_eq!(disjunctive_normal_form("A B C atleast5/3 !"), "1");
+        assert_eq!(disjunctive_normal_form("x12 x12 |"), "x12 ");
+        let parser = Parser::new();
+        assert!(parser
+            .truth_table_from(&disjunctive_normal_form("x12 x12 |"))
+            .is_ok());
+    }
+
+    #[test]
+    fn dont_cares() {
+        let parser = Parser::new();
+        let variables = "ABCD".chars().map(String::from).collect::<Vec<_>>();
+        let table = TruthTable::from_sets(variables, 5..10, 10..16).unwrap();
+        for strategy in [Minimiser::Exact, Minimiser::Espresso] {
+            let dnf = parser.dnf_from_table(&table, strategy);
+            assert_eq!(dnf.to_rpn(), "BD&BC&A||");
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use rsb::formula::Formula;
+
+fn to_infix(formula: &str) -> String {
+    match Formula::parse_rpn(formula) {
+        Ok(formula) => formula.to_infix(),
+        _ => String::from(formula),
+    }
+}
+
+fn to_rpn(formula: &str) -> String {
+    match Formula::parse_infix(formula) {
+        Ok(formula) => formula.to_rpn(),
+        _ => String::from(formula),
+    }
+}
+
+#[allow(dead_code)]
+fn main() {
+    println!("{}", to_infix("AB&C!|"));
+    println!("{}", to_rpn("(A & B) | !C"));
+}
+
+#[cfg(test)]
+mod infix_notation {
+    use super::*;
+    use rsb::error::ParseErrorKind;
+
+    #[test]
+    fn assert_equal() {
+        assert_eq!(to_infix("AB&C!|"), "A & B | !C");
+        assert_eq!(to_rpn("(A & B) | !C"), "AB&C!|");
+        assert_eq!(to_rpn("A -> B <-> C"), "AB>C=");
+        assert_eq!(to_infix("AB>C="), "A -> B <-> C");
+        assert_eq!(to_rpn("!(A | 1) ^ 0"), "A1|!0^");
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(to_rpn("A | B & C"), "ABC&|");
+        assert_eq!(to_rpn("A & B | C"), "AB&C|");
+        assert_eq!(to_rpn("A ^ B & C | D"), "ABC&^D|");
+        assert_eq!(to_rpn("A | B -> C & D"), "AB|CD&>");
+        assert_eq!(to_rpn("!A & B"), "A!B&");
+        assert_eq!(to_rpn("!(A & B)"), "AB&!");
+        assert_eq!(to_rpn("A > B = C"), "AB>C=");
+    }
+
+    #[test]
+    fn associativity() {
+        assert_eq!(to_rpn("A & B & C"), "AB&C&");
+        assert_eq!(to_rpn("A | B | C"), "AB|C|");
+        assert_eq!(to_rpn("A ^ B ^ C"), "AB^C^");
+        assert_eq!(to_rpn("A -> B -> C"), "ABC>>");
+        assert_eq!(to_rpn("A <-> B <-> C"), "AB=C=");
+    }
+
+    #[test]
+    fn minimal_parentheses() {
+        assert_eq!(to_infix("ABC&&"), "A & (B & C)");
+        assert_eq!(to_infix("AB&C&"), "A & B & C");
+        assert_eq!(to_infix("ABC>>"), "A -> B -> C");
+        assert_eq!(to_infix("AB>C>"), "(A -> B) -> C");
+        assert_eq!(to_infix("AB|C&"), "(A | B) & C");
+        assert_eq!(to_infix("AB&!!"), "!!(A & B)");
+        assert_eq!(to_infix("A!!B|"), "!!A | B");
+    }
+
+    #[test]
+    fn round_trip() {
+        for formula in [
+            "A!B&!C|!D!&!E!&!A>!!!F=G!&",
+            "ABC^^",
+            "AB=!CD>|",
+            "AB&!CD&!>!",
+        ] {
+            assert_eq!(to_rpn(&to_infix(formula)), formula);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Formula::parse_infix("A &").is_err());
+        assert!(Formula::parse_infix("(A | B").is_err());
+        assert!(Formula::parse_infix("A B").is_err());
+        assert!(Formula::parse_infix("A ? B").is_err());
+        assert!(Formula::parse_infix(")").is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = Formula::parse_infix("(A | B").unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedEnd);
+        assert_eq!((error.offset, error.found, error.depth), (6, None, 1));
+        assert_eq!(error.expected, "an operator or ')'");
+
+        let error = Formula::parse_infix("A & (B | ?)").unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedCharacter);
+        assert_eq!((error.offset, error.found, error.depth), (9, Some('?'), 1));
+        assert_eq!(error.render(), "A & (B | ?)\n         ^");
+
+        let error = Formula::parse_infix("A B").unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedToken);
+        assert_eq!((error.offset, error.found, error.depth), (2, Some('B'), 0));
+
+        let error = Formula::parse_infix("A & )").unwrap_err();
+        assert_eq!((error.offset, error.found), (4, Some(')')));
+        assert_eq!(error.expected, "a variable, a constant, '!' or '('");
+    }
+
+    #[test]
+    fn variable_names() {
+        assert_eq!(
+            to_rpn("req_valid & !s[3] | x12"),
+            "req_valid s[3] ! & x12 |"
+        );
+        assert_eq!(to_infix("req_valid s[3]!& x12|"), "req_valid & !s[3] | x12");
+        assert_eq!(to_rpn("AB & C"), "AB C &");
+        assert_eq!(to_infix("AB&"), "A & B");
+        assert_eq!(to_infix("AB C &"), "AB & C");
+        assert!(Formula::parse_infix("s[3").is_err());
+        assert!(Formula::parse_rpn("x y-1 &").is_err());
+    }
+
+    #[test]
+    fn rpn_symbols() {
+        for (symbol, spelled) in [(">", "->"), ("=", "<->"), ("<", "<-")] {
+            let formula = Formula::parse_infix(&format!("A {} B", symbol)).unwrap();
+            assert_eq!(
+                formula,
+                Formula::parse_infix(&format!("A {} B", spelled)).unwrap()
+            );
+            assert_eq!(formula.to_infix(), format!("A {} B", spelled));
+        }
+        assert_eq!(to_infix(&to_rpn("A > B > C")), "A -> B -> C");
+        assert_eq!(to_infix(&to_rpn("A | B = C & D")), "A | B <-> C & D");
+    }
+
+    #[test]
+    fn connectives() {
+        assert_eq!(to_rpn("A ↑ B ↓ C"), "AB↑C↓");
+        assert_eq!(to_rpn("A & B ↑ C"), "AB&C↑");
+        assert_eq!(to_rpn("A <- B | C"), "ABC|<");
+        assert_eq!(to_rpn("A < B"), "AB<");
+        assert_eq!(to_rpn("amo(A, B, C)"), "A B C amo/3");
+        assert_eq!(
+            to_rpn("!atleast2(A, B & C, !D) | E"),
+            "A B C & D ! atleast2/3 ! E |"
+        );
+        assert_eq!(to_infix("A B C maj/3 D &"), "maj(A, B, C) & D");
+        assert_eq!(to_infix("AB↑C↑"), "A ↑ B ↑ C");
+        assert_eq!(to_infix("ABC↑↑"), "A ↑ (B ↑ C)");
+        assert_eq!(to_infix("A B | C exo/2"), "exo(A | B, C)");
+        assert!(Formula::parse_infix("amo(A, B").is_err());
+        assert!(Formula::parse_infix("amo(A,)").is_err());
+        assert!(Formula::parse_infix("amo(, A)").is_err());
+
+        // Without operands, in both notations
+        for (infix, rpn) in [
+            ("amo()", "amo/0 "),
+            ("A & exo()", "A exo/0 &"),
+            ("maj(atleast0(), B)", "atleast0/0 B maj/2"),
+        ] {
+            let formula = Formula::parse_infix(infix).unwrap();
+            assert_eq!(formula.to_rpn(), rpn);
+            assert_eq!(Formula::parse_rpn(rpn).unwrap(), formula);
+            assert_eq!(formula.to_infix(), infix);
+        }
+    }
+
+    #[test]
+    fn deep_formulas() {
+        // Parsed and printed without recursing, however deep the formula is
+        let depth = 100000;
+        let negations = format!("{}A", "!".repeat(depth));
+        let formula = Formula::parse_infix(&negations).unwrap();
+        assert_eq!(formula.to_infix(), negations);
+        assert_eq!(to_infix(&formula.to_rpn()), negations);
+
+        let nested = format!("{}A{}", "(A & ".repeat(depth), ")".repeat(depth));
+        let formula = Formula::parse_infix(&nested).unwrap();
+        assert_eq!(formula.to_rpn(), "A".repeat(depth + 1) + &"&".repeat(depth));
+        assert_eq!(formula.to_infix(), nested[1..nested.len() - 1]);
+
+        let chain = format!("A{}", " -> A".repeat(depth));
+        assert_eq!(Formula::parse_infix(&chain).unwrap().to_infix(), chain);
+
+        let error = Formula::parse_infix(&format!("{}A", "amo(".repeat(depth))).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedEnd);
+        assert_eq!(error.depth, depth);
+    }
+}
@@ -1,4 +1,3 @@
-use rsb::map;
 use rsb::reverse_map;
 
 #[allow(dead_code)]
@@ -9,6 +8,7 @@ fn main() {
 #[cfg(test)]
 mod curve {
     use super::*;
+    use rsb::map;
 
     #[test]
     fn assert_equal() {
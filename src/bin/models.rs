@@ -0,0 +1,127 @@
+use rsb::parser::Parser;
+
+fn models(formula: &str) -> Vec<String> {
+    let parser = Parser::new();
+    let mut models = match parser.models(formula) {
+        Ok(models) => models.map(|model| model.to_string()).collect(),
+        _ => Vec::new(),
+    };
+    models.sort();
+    models
+}
+
+fn count(formula: &str) -> u128 {
+    let parser = Parser::new();
+    parser.count_models(formula).unwrap_or(0)
+}
+
+#[allow(dead_code)]
+fn main() {
+    models("AB|C&");
+    count("AB|C&");
+}
+
+#[cfg(test)]
+mod models {
+    use super::*;
+    use rsb::assignment::Assignment;
+    use rsb::cnf::Cnf;
+    use rsb::models;
+    use std::collections::HashSet;
+
+    const FORMULAS: [&str; 10] = [
+        "AB|C&",
+        "AB^C=",
+        "AB&CD&|EF&|",
+        "A B C D E exo/5",
+        "A B C D E F atleast3/6 A B ^ &",
+        "AB>BC>&CA>&",
+        "AB↑C↓D<E|",
+        "AA!&",
+        "AB=CD=&EF=&A!F&&",
+        "A B C D amo/4 E F G H amo/4 &",
+    ];
+
+    #[test]
+    fn enumerate() {
+        let parser = Parser::new();
+        for formula in FORMULAS {
+            let table = parser.truth_table_from(formula).unwrap();
+            let expected = table
+                .ones()
+                .map(|row| {
+                    let values = table.assignment_of(row).collect::<Vec<_>>();
+                    Assignment::from_values(&table.variables, &values)
+                })
+                .collect::<HashSet<_>>();
+            let found = parser.models(formula).unwrap().collect::<Vec<_>>();
+            assert_eq!(found.len(), expected.len(), "{}", formula);
+            assert_eq!(found.into_iter().collect::<HashSet<_>>(), expected);
+        }
+        assert_eq!(models("AB&C!&"), ["{A: 1, B: 1, C: 0}"]);
+        assert_eq!(models("AB1|&"), ["{A: 1, B: 0}", "{A: 1, B: 1}"]);
+        assert_eq!(models("A1|"), ["{A: 0}", "{A: 1}"]);
+        assert_eq!(models("1"), ["{}"]);
+        assert_eq!(models("0"), Vec::<String>::new());
+        assert_eq!(models("A0&"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn lazy() {
+        // 2^80 - 1 models, of which only the first few are found
+        let formula = (1..=80)
+            .map(|i| format!("x{}", i))
+            .collect::<Vec<_>>()
+            .join(" ")
+            + &" |".repeat(79);
+        let parser = Parser::new();
+        let first = parser.models(&formula).unwrap().take(5).collect::<Vec<_>>();
+        assert_eq!(first.len(), 5);
+        assert_eq!(first.iter().collect::<HashSet<_>>().len(), 5);
+        assert!(first.iter().all(|model| model.len() == 80));
+        assert!(first.iter().all(|model| model.iter().any(|(_, v)| v)));
+        assert_eq!(count(&formula), (1 << 80) - 1);
+    }
+
+    #[test]
+    fn counting() {
+        let parser = Parser::new();
+        for formula in FORMULAS {
+            let table = parser.truth_table_from(formula).unwrap();
+            assert_eq!(count(formula), table.count_ones() as u128, "{}", formula);
+        }
+        assert_eq!(count("1"), 1);
+        assert_eq!(count("0"), 0);
+        assert_eq!(count("AB1|&"), 2);
+
+        // 40 independent pairs with one of two values each
+        let pairs = (0..40)
+            .map(|i| format!("a{i} b{i} ^"))
+            .collect::<Vec<_>>()
+            .join(" ")
+            + &" &".repeat(39);
+        assert_eq!(count(&pairs), 1 << 40);
+
+        // At most one of 12, and exactly one of 16
+        let names = |n: usize| (0..n).map(|i| format!("x{i}")).collect::<Vec<_>>();
+        assert_eq!(count(&format!("{} amo/12", names(12).join(" "))), 13);
+        assert_eq!(count(&format!("{} exo/16", names(16).join(" "))), 16);
+
+        let cnf = Cnf::from_dimacs("p cnf 5 2\n1 2 0\n-1 3 0\n").unwrap();
+        assert_eq!(models::count(&cnf), Some(16));
+
+        // Only a count past u128::MAX is rejected, not the number of variables
+        let formula = format!("{} amo/128", names(128).join(" "));
+        assert_eq!(parser.count_models(&formula).unwrap(), 129);
+        let tautologies = |n: usize| {
+            names(n)
+                .iter()
+                .map(|x| format!("{x} {x} ! |"))
+                .collect::<Vec<_>>()
+                .join(" ")
+                + &" &".repeat(n - 1)
+        };
+        assert_eq!(parser.count_models(&tautologies(127)).unwrap(), 1 << 127);
+        assert!(parser.count_models(&tautologies(128)).is_err());
+    }
+}
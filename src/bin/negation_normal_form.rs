@@ -1,7 +1,7 @@
 use rsb::parser::Parser;
 
 fn negation_normal_form(formula: &str) -> String {
-    let mut parser = Parser::new();
+    let parser = Parser::new();
     if let Ok(nnf) = parser.evaluate_nnf(formula) {
         return nnf;
     }
@@ -18,12 +18,12 @@ mod negation_normal_form {
     use super::*;
 
     fn assert_equal_truthtable(formula: &str) -> Option<String> {
-        let mut parser = Parser::new();
-        if let Ok(table1) = parser.truth_table_from(&formula) {
-            if let Ok(nnf) = parser.evaluate_nnf(&formula) {
+        let parser = Parser::new();
+        if let Ok(table1) = parser.truth_table_from(formula) {
+            if let Ok(nnf) = parser.evaluate_nnf(formula) {
                 if let Ok(table2) = parser.truth_table_from(&nnf) {
                     assert_eq!(table1, table2);
-                    return Some(String::from(nnf));
+                    return Some(nnf);
                 }
             }
         }
@@ -115,4 +115,51 @@ mod negation_normal_form {
         );
         assert_eq!(assert_equal_truthtable("ABC>>").unwrap(), "A!B!C||");
     }
+
+    #[test]
+    fn constants() {
+        assert_eq!(assert_equal_truthtable("A1&").unwrap(), "A");
+        assert_eq!(assert_equal_truthtable("A0|B&").unwrap(), "AB&");
+        assert_eq!(assert_equal_truthtable("A1&B|").unwrap(), "AB|");
+        assert_eq!(assert_equal_truthtable("A0^!").unwrap(), "A!");
+        assert_eq!(assert_equal_truthtable("1A>B0|=").unwrap(), "A!B|B!A|&");
+        assert_eq!(negation_normal_form("A1|"), "1");
+        assert_eq!(negation_normal_form("AB&0&"), "0");
+        assert_eq!(negation_normal_form("10>!"), "1");
+    }
+
+    #[test]
+    fn connectives() {
+        assert_eq!(assert_equal_truthtable("AB↑").unwrap(), "A!B!|");
+        assert_eq!(assert_equal_truthtable("AB↓").unwrap(), "A!B!&");
+        assert_eq!(assert_equal_truthtable("AB<").unwrap(), "AB!|");
+        assert_eq!(assert_equal_truthtable("AB↑!").unwrap(), "AB&");
+        assert_eq!(assert_equal_truthtable("AB↓!").unwrap(), "AB|");
+        assert_eq!(assert_equal_truthtable("AB<!").unwrap(), "A!B&");
+        assert_eq!(
+            assert_equal_truthtable("A B C amo/3").unwrap(),
+            "A!B!|A!C!|B!C!|&&"
+        );
+        assert_equal_truthtable("A B C exo/3").unwrap();
+        assert_equal_truthtable("A B C exo/3 !").unwrap();
+        assert_equal_truthtable("A B C maj/3").unwrap();
+        assert_equal_truthtable("A B C D atleast2/4 !").unwrap();
+        assert_equal_truthtable("A B C D ↑ E 1 atleast3/4 B C amo/2 ↓ &").unwrap();
+    }
+
+    #[test]
+    fn large_thresholds() {
+        let operands = |n: usize| {
+            (0..n)
+                .map(|i| format!("x{}", i))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        assert_equal_truthtable(&format!("{} maj/12", operands(12))).unwrap();
+        assert_equal_truthtable(&format!("{} atleast5/13 !", operands(13))).unwrap();
+        // The majority of 24 operands has C(24, 13) = 2496144 minimal terms,
+        // while splitting the operands in halves needs a few thousand nodes
+        let nnf = negation_normal_form(&format!("{} maj/24", operands(24)));
+        assert!(nnf.split(' ').count() < 10_000);
+    }
 }
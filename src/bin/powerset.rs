@@ -2,7 +2,8 @@ fn powerset(set: &[i32]) -> Vec<Vec<i32>> {
     (0..((2i32).pow(set.len() as u32)))
         .map(|c| {
             (0..(set.len()))
-                .filter_map(|bit| (c & (1 << bit) != 0).then(|| set[bit]))
+                .filter(|&bit| c & (1 << bit) != 0)
+                .map(|bit| set[bit])
                 .collect()
         })
         .collect()
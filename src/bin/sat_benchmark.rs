@@ -0,0 +1,194 @@
+use rsb::cdcl;
+use rsb::cnf::{Clause, Cnf, Lit};
+use rsb::dpll;
+use rsb::evaluator::Evaluator;
+use rsb::formula::Formula;
+use rsb::parser::Parser;
+use rsb::random::Random;
+use rsb::tseitin::Encoding;
+use std::time::{Duration, Instant};
+
+// Brute force evaluates 64 rows at a time, which stops being practical
+// around this many variables
+const BRUTE_FORCE_MAX: usize = 24;
+
+fn variables(prefix: &str, len: usize) -> Vec<String> {
+    (1..=len).map(|i| format!("{}{}", prefix, i)).collect()
+}
+
+// Uniform random 3-SAT with `clauses` clauses over `len` variables, which is
+// hardest around 4.26 clauses per variable
+fn random_3sat(len: usize, clauses: usize, seed: u64) -> Cnf {
+    let mut random = Random::new(seed);
+    let mut cnf = Cnf::new(variables("x", len));
+    for _ in 0..clauses {
+        let clause = (0..3)
+            .map(|_| {
+                Lit::new(
+                    random.next_usize() % len,
+                    random.next_usize().is_multiple_of(2),
+                )
+            })
+            .collect::<Clause>();
+        cnf.push(clause);
+    }
+    cnf
+}
+
+// `holes + 1` pigeons in `holes` holes, which is unsatisfiable and hard for
+// resolution
+fn pigeonhole(holes: usize) -> Cnf {
+    let var = |pigeon: usize, hole: usize| pigeon * holes + hole;
+    let mut cnf = Cnf::new(variables("p", (holes + 1) * holes));
+    for pigeon in 0..=holes {
+        cnf.push(
+            (0..holes)
+                .map(|hole| Lit::positive(var(pigeon, hole)))
+                .collect(),
+        );
+    }
+    for hole in 0..holes {
+        for a in 0..=holes {
+            for b in a + 1..=holes {
+                cnf.push(Clause::new(vec![
+                    Lit::negative(var(a, hole)),
+                    Lit::negative(var(b, hole)),
+                ]));
+            }
+        }
+    }
+    cnf
+}
+
+fn brute_force(cnf: &Cnf) -> bool {
+    Evaluator::new(&cnf.to_formula())
+        .words()
+        .any(|word| word != 0)
+}
+
+fn time(solve: impl FnOnce() -> bool) -> (bool, Duration) {
+    let start = Instant::now();
+    let satisfiable = solve();
+    (satisfiable, start.elapsed())
+}
+
+// `formula` is the formula the CNF encodes, if any, which is also timed
+// end to end through `Parser::is_satisfiable`
+fn benchmark(name: &str, cnf: &Cnf, formula: Option<&str>) {
+    let print = |(satisfiable, duration): (bool, Duration)| {
+        format!("{:>5} {:>10.3?}", satisfiable, duration)
+    };
+    let parser = match formula {
+        Some(formula) => print(time(|| Parser::new().is_satisfiable(formula))),
+        None => String::from("-"),
+    };
+    let brute = match cnf.variables.len() <= BRUTE_FORCE_MAX {
+        true => print(time(|| brute_force(cnf))),
+        false => String::from("-"),
+    };
+    let dpll = print(time(|| dpll::is_satisfiable(cnf)));
+    let mut solver = cdcl::Solver::new(cnf);
+    let cdcl = print(time(|| solver.solve().is_some()));
+    println!(
+        "{:<24} {:>5} {:>6} {:>16} {:>16} {:>16} {:>16}  {:?}",
+        name,
+        cnf.variables.len(),
+        cnf.len(),
+        parser,
+        brute,
+        dpll,
+        cdcl,
+        solver.stats()
+    );
+}
+
+fn main() {
+    println!(
+        "{:<24} {:>5} {:>6} {:>16} {:>16} {:>16} {:>16}",
+        "instance", "vars", "claus", "is_satisfiable", "brute force", "dpll", "cdcl"
+    );
+    let majority = format!("{} maj/24", variables("x", 24).join(" "));
+    let formulas = [
+        ("AB&A!B!&&", "AB&A!B!&&"),
+        ("ABCDE^^^^", "ABCDE^^^^"),
+        ("AA!^0>", "AA!^0>"),
+        ("A B C D E F G H exo/8", "A B C D E F G H exo/8"),
+        ("x1 ... x24 maj/24", majority.as_str()),
+    ];
+    for (name, formula) in formulas {
+        let cnf = Formula::parse_rpn(formula)
+            .unwrap()
+            .tseitin(Encoding::Polarity)
+            .cnf;
+        benchmark(name, &cnf, Some(formula));
+    }
+    for len in [20, 24, 50, 100, 150, 175] {
+        let clauses = len * 426 / 100;
+        benchmark(
+            &format!("3-SAT {}/{}", len, clauses),
+            &random_3sat(len, clauses, len as u64),
+            None,
+        );
+    }
+    for holes in [4, 6, 7, 8] {
+        benchmark(
+            &format!("pigeonhole {}/{}", holes + 1, holes),
+            &pigeonhole(holes),
+            None,
+        );
+    }
+}
+
+#[cfg(test)]
+mod sat_benchmark {
+    use super::*;
+
+    #[test]
+    fn agrees_with_brute_force() {
+        for len in 3..=14 {
+            for seed in 1..=20 {
+                let cnf = random_3sat(len, len * 426 / 100, seed * 7919 + len as u64);
+                let model = cdcl::solve(&cnf);
+                assert_eq!(model.is_some(), brute_force(&cnf), "{}", cnf);
+                assert_eq!(model.is_some(), dpll::is_satisfiable(&cnf));
+                if let Some(model) = model {
+                    assert!(cnf.evaluate(&model));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pigeonholes() {
+        for holes in 1..=6 {
+            let mut solver = cdcl::Solver::new(&pigeonhole(holes));
+            assert_eq!(solver.solve(), None);
+            assert!(holes == 1 || solver.stats().learnt > 0);
+        }
+        let cnf = Cnf::from_dimacs("p cnf 4 4\n1 2 0\n-1 2 0\n1 -2 0\n3 4 0\n").unwrap();
+        let model = cdcl::solve(&cnf).unwrap();
+        assert_eq!(model[..2], [true, true]);
+        assert!(cnf.evaluate(&model));
+        assert_eq!(cdcl::solve(&Cnf::constant(true)), Some(vec![]));
+        assert_eq!(cdcl::solve(&Cnf::constant(false)), None);
+    }
+
+    #[test]
+    fn larger_instances() {
+        // Below the threshold random instances are almost always satisfiable,
+        // above it almost always unsatisfiable
+        let cnf = random_3sat(300, 1050, 42);
+        let model = cdcl::solve(&cnf).unwrap();
+        assert!(cnf.evaluate(&model));
+        let mut solver = cdcl::Solver::new(&random_3sat(100, 600, 42));
+        assert_eq!(solver.solve(), None);
+        let stats = solver.stats();
+        assert!(stats.restarts > 0, "{:?}", stats);
+        assert!(stats.conflicts > stats.restarts);
+
+        // Enough conflicts for some learnt clauses to be deleted
+        let mut solver = cdcl::Solver::new(&random_3sat(150, 639, 150));
+        assert_eq!(solver.solve(), None);
+        assert!(solver.stats().deleted > 0, "{:?}", solver.stats());
+    }
+}
@@ -1,7 +1,7 @@
 use rsb::parser::Parser;
 
 fn eval_set(formula: &str, sets: &[&[i32]]) -> Vec<i32> {
-    let mut parser = Parser::new();
+    let parser = Parser::new();
     if let Ok(set) = parser.evaluate_set(formula, sets) {
         return set;
     }
@@ -107,4 +107,49 @@ mod set_evaluation {
         assert_eq!(eval_set("ABC^^", &[&[0], &[0], &[0]]), [0]);
         assert_eq!(eval_set("ABC>>", &[&[0], &[0], &[0]]), [0]);
     }
+
+    #[test]
+    fn constants() {
+        assert_eq!(eval_set("1", &[]), []);
+        assert_eq!(eval_set("0", &[]), []);
+        assert_eq!(eval_set("A1&", &[&[0, 1]]), [0, 1]);
+        assert_eq!(eval_set("A0|B|", &[&[0, 1], &[2]]), [0, 1, 2]);
+        assert_eq!(eval_set("A1^B&", &[&[0, 1], &[1, 2]]), [2]);
+        assert_eq!(eval_set("A0>B|", &[&[0, 1], &[1, 2, 3]]), [1, 2, 3]);
+        assert_eq!(eval_set("A1>B&", &[&[0, 1], &[1, 2, 3]]), [1, 2, 3]);
+    }
+
+    #[test]
+    fn connectives() {
+        assert_eq!(eval_set("AB↑", &[&[0, 1, 2], &[0, 3]]), [1, 2, 3]);
+        assert_eq!(eval_set("AB↓C|", &[&[0, 1], &[1, 2], &[3]]), [3]);
+        assert_eq!(eval_set("AB↓", &[&[0, 1], &[1, 2]]), []);
+        assert_eq!(eval_set("AB<", &[&[0, 1, 2], &[0, 3]]), [0, 1, 2]);
+        assert_eq!(
+            eval_set("A B C exo/3", &[&[0, 1], &[1, 2], &[2, 3]]),
+            [0, 3]
+        );
+        assert_eq!(
+            eval_set("A B C maj/3", &[&[0, 1], &[1, 2], &[2, 3]]),
+            [1, 2]
+        );
+        assert_eq!(
+            eval_set("A B C amo/3", &[&[0, 1], &[1, 2], &[1, 3]]),
+            [0, 2, 3]
+        );
+        assert_eq!(
+            eval_set("A B C atleast3/3", &[&[0, 1], &[1, 2], &[1, 3]]),
+            [1]
+        );
+    }
+
+    #[test]
+    fn deep_formulas() {
+        // Nested deeper than a recursive evaluation fits in the stack of a
+        // test thread
+        let negations = format!("A{}", "!".repeat(5000));
+        assert_eq!(eval_set(&negations, &[&[0, 1]]), [0, 1]);
+        let negations = format!("A{}", "!".repeat(5001));
+        assert_eq!(eval_set(&negations, &[&[0, 1]]), []);
+    }
 }
@@ -1,7 +1,7 @@
 use rsb::parser::Parser;
 
 fn print_truth_table(formula: &str) {
-    let mut parser = Parser::new();
+    let parser = Parser::new();
     if let Ok(table) = parser.truth_table_from(formula) {
         print!("{}", table);
     }
@@ -15,6 +15,9 @@ fn main() {
 #[cfg(test)]
 mod truth_table {
     use super::*;
+    use rsb::evaluator::Evaluator;
+    use rsb::formula::Formula;
+    use rsb::truthtable::{Output, TruthTable};
 
     #[test]
     fn assert_equal() {
@@ -23,4 +26,254 @@ mod truth_table {
             ()
         );
     }
+
+    #[test]
+    fn variable_names() {
+        let parser = Parser::new();
+        let table = parser.truth_table_from("x12 req_valid & s[3] |").unwrap();
+        assert_eq!(table.variables, ["req_valid", "s[3]", "x12"]);
+        assert_eq!(
+            table.to_string(),
+            "| req_valid | s[3] | x12 | = |\n\
+             |-----------|------|-----|---|\n\
+             | 0         | 0    | 0   | 0 |\n\
+             | 0         | 0    | 1   | 0 |\n\
+             | 0         | 1    | 0   | 1 |\n\
+             | 0         | 1    | 1   | 1 |\n\
+             | 1         | 0    | 0   | 0 |\n\
+             | 1         | 0    | 1   | 1 |\n\
+             | 1         | 1    | 0   | 1 |\n\
+             | 1         | 1    | 1   | 1 |\n"
+        );
+
+        let table = parser
+            .truth_table_from("x2 x12 x1 | s[10] s[9] ^ & &")
+            .unwrap();
+        assert_eq!(table.variables, ["s[9]", "s[10]", "x1", "x2", "x12"]);
+
+        // A single variable prints in whitespace mode and parses back
+        for name in ["x12", "req_valid", "s[3]"] {
+            let formula = Formula::var(name);
+            assert_eq!(formula.to_rpn(), format!("{} ", name));
+            assert_eq!(Formula::parse_rpn(&formula.to_rpn()).unwrap(), formula);
+            assert!(Formula::parse_rpn(name).is_err());
+        }
+        assert_eq!(Formula::var("A").to_rpn(), "A");
+        assert_eq!(parser.evaluate_nnf("x12 ! !").unwrap(), "x12 ");
+        assert!(parser.is_satisfiable("x12 "));
+        // Compact input is still read one character at a time
+        assert!(Formula::parse_rpn("AB").is_err());
+        assert_eq!(Formula::parse_rpn("A1&").unwrap().to_rpn(), "A1&");
+        assert!(!parser.is_satisfiable("AB"));
+    }
+
+    #[test]
+    fn more_than_26_variables() {
+        let formula = (0..30)
+            .map(|i| format!("x{}", i))
+            .collect::<Vec<_>>()
+            .join(" ")
+            + &" |".repeat(29);
+        let formula = Formula::parse_rpn(&formula).unwrap();
+        assert_eq!(formula.variables().len(), 30);
+        assert_eq!(formula.variables()[29], "x29");
+        assert_eq!(Formula::parse_rpn(&formula.to_rpn()).unwrap(), formula);
+    }
+
+    #[test]
+    fn constants() {
+        let parser = Parser::new();
+        let table = parser.truth_table_from("A1&B|").unwrap();
+        assert_eq!(table.variables, ["A", "B"]);
+        assert_eq!(table.iter().collect::<Vec<_>>(), [false, true, true, true]);
+        let table = parser.truth_table_from("10|").unwrap();
+        assert!(table.variables.is_empty());
+        assert_eq!(table.iter().collect::<Vec<_>>(), [true]);
+    }
+
+    #[test]
+    fn bit_packed() {
+        let formula = (0..20)
+            .map(|i| format!("x{}", i))
+            .collect::<Vec<_>>()
+            .join(" ")
+            + &" ^".repeat(19);
+        let parser = Parser::new();
+        let table = parser.truth_table_from(&formula).unwrap();
+        assert_eq!(table.row_count(), 1 << 20);
+        assert_eq!(table.count_ones(), 1 << 19);
+        assert_eq!(table.ones().next(), Some(1));
+        assert!(table.ones().all(|row| row.count_ones() % 2 == 1));
+
+        let mut values = [false; 20];
+        assert!(!table.lookup(&values));
+        values[7] = true;
+        assert!(table.lookup(&values));
+        let assignment = table
+            .variables
+            .iter()
+            .map(|v| (v.clone(), v == "x3"))
+            .collect();
+        assert!(table.lookup_assignment(&assignment).unwrap());
+        assert!(table.lookup_assignment(&Default::default()).is_err());
+
+        let variables = table.variables.clone();
+        let parity = TruthTable::from_fn(variables, |values| {
+            values.iter().filter(|b| **b).count() % 2 == 1
+        });
+        assert_eq!(parity, table);
+    }
+
+    #[test]
+    fn from_fn() {
+        let variables = vec![String::from("A"), String::from("B"), String::from("C")];
+        let mut table = TruthTable::from_fn(variables, |v| v[0] & !v[2]);
+        assert_eq!(table.ones().collect::<Vec<_>>(), [4, 6]);
+        assert!(table.get(table.row_of(&[true, true, false])));
+        assert_eq!(
+            table.assignment_of(6).collect::<Vec<_>>(),
+            [true, true, false]
+        );
+        table.set(6, false);
+        table.set(0, true);
+        assert_eq!(table.ones().collect::<Vec<_>>(), [0, 4]);
+        assert_eq!(
+            table,
+            TruthTable::from_formula(&Formula::parse_rpn("AC!&B!&AB|C|!|").unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn compiled_evaluator() {
+        let formula = Formula::parse_rpn(
+            "a b c d ↑ e f g h ^ > = i j k amo/3 & | l m n o p maj/5 x1 x2 | atleast2/3 ^ ↓ \
+             x3 x4 | x5 ! x6 x7 x8 exo/4 < &",
+        )
+        .unwrap();
+        let evaluator = Evaluator::new(&formula);
+        assert_eq!(evaluator.variables().len(), 24);
+        let table = TruthTable::from_formula(&formula).unwrap();
+        for row in (0..table.row_count()).step_by(9973) {
+            let values = table.assignment_of(row).collect::<Vec<_>>();
+            let assigned = formula.replace_variables(&|name| {
+                let i = table.variables.iter().position(|v| v == name)?;
+                Some(Formula::Const(values[i]))
+            });
+            assert_eq!(evaluator.evaluate(&values), table.get(row));
+            assert_eq!(assigned.evaluate().unwrap(), table.get(row));
+        }
+
+        // Compiled without recursing, however deep the formula is
+        let parser = Parser::new();
+        let table = parser
+            .truth_table_from(&format!("A{}", "!".repeat(20000)))
+            .unwrap();
+        assert_eq!(table.ones().collect::<Vec<_>>(), [1]);
+
+        // More operands required than there are, even with one to spare
+        for (formula, ones) in [
+            ("A B atleast3/2", 0),
+            ("A B atleast4/2", 0),
+            ("A B C atleast9/3 !", 8),
+        ] {
+            let table = TruthTable::from_formula(&Formula::parse_rpn(formula).unwrap()).unwrap();
+            assert_eq!(table.count_ones(), ones, "{}", formula);
+        }
+        let formula = Formula::parse_infix("atleast4(A, B) | C").unwrap();
+        let table = TruthTable::from_formula(&formula).unwrap();
+        assert_eq!(table.ones().collect::<Vec<_>>(), [1, 3, 5, 7]);
+    }
+
+    #[test]
+    fn dont_cares() {
+        let variables = vec![String::from("A"), String::from("B")];
+        let mut table = TruthTable::from_sets(variables.clone(), [1, 2], [3]).unwrap();
+        let error = TruthTable::from_sets(variables.clone(), [1, 4], []).unwrap_err();
+        assert_eq!(error.to_string(), "Index 4 out of range for 2 variables");
+        assert!(TruthTable::from_sets(variables.clone(), [], [0, 7]).is_err());
+        let many = (0..33).map(|i| format!("x{}", i)).collect::<Vec<_>>();
+        let error = TruthTable::from_sets(many.clone(), [0], []).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "33 variables, a truth table holds at most 32"
+        );
+        assert!(TruthTable::from_index_list(many, "Σm(0)").is_err());
+        assert_eq!(
+            table.outputs().collect::<Vec<_>>(),
+            [Output::False, Output::True, Output::True, Output::DontCare]
+        );
+        assert_eq!(table.iter().collect::<Vec<_>>(), [false, true, true, false]);
+        assert_eq!(table.ones().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(table.zeros().collect::<Vec<_>>(), [0]);
+        assert_eq!(table.dont_cares().collect::<Vec<_>>(), [3]);
+        assert!(table.has_dont_cares());
+        assert_eq!(
+            table.to_string(),
+            "| A | B | = |\n\
+             |---|---|---|\n\
+             | 0 | 0 | 0 |\n\
+             | 0 | 1 | 1 |\n\
+             | 1 | 0 | 1 |\n\
+             | 1 | 1 | X |\n"
+        );
+
+        let parser = Parser::new();
+        assert!(table.is_implemented_by(&parser.truth_table_from("AB|").unwrap()));
+        assert!(table.is_implemented_by(&parser.truth_table_from("AB^").unwrap()));
+        assert!(!table.is_implemented_by(&parser.truth_table_from("AB&").unwrap()));
+        assert!(!table.is_implemented_by(&parser.truth_table_from("AC|").unwrap()));
+
+        table.set(3, true);
+        assert!(!table.has_dont_cares());
+        assert_eq!(table, parser.truth_table_from("AB|").unwrap());
+    }
+
+    #[test]
+    fn canonical_forms() {
+        let parser = Parser::new();
+        let table = parser.truth_table_from("AB&C|").unwrap();
+        assert_eq!(table.minterm_list(), "Σm(1,3,5,6,7)");
+        assert_eq!(table.maxterm_list(), "ΠM(0,2,4)");
+        assert_eq!(
+            table.canonical_dnf().to_rpn(),
+            "A!B!C&&A!BC&&AB!C&&ABC!&&ABC&&||||"
+        );
+        assert_eq!(table.canonical_cnf().to_rpn(), "ABC||AB!C||A!BC||&&");
+        assert_eq!(
+            parser
+                .truth_table_from(&table.canonical_dnf().to_rpn())
+                .unwrap(),
+            table
+        );
+        assert_eq!(
+            parser
+                .truth_table_from(&table.canonical_cnf().to_rpn())
+                .unwrap(),
+            table
+        );
+
+        let variables = || vec![String::from("A"), String::from("B"), String::from("C")];
+        let from = |list: &str| TruthTable::from_index_list(variables(), list);
+        assert_eq!(from("Σm(1,3,5,6,7)").unwrap(), table);
+        assert_eq!(from("ΠM(0, 2, 4)").unwrap(), table);
+        assert_eq!(from("m(1,3,5,6,7)").unwrap(), table);
+        assert_eq!(from("M(0,2,4)").unwrap(), table);
+
+        let table = from("Σm(1,3) + d(5,7)").unwrap();
+        assert_eq!(table.dont_cares().collect::<Vec<_>>(), [5, 7]);
+        assert_eq!(table.minterm_list(), "Σm(1,3) + d(5,7)");
+        assert_eq!(table.maxterm_list(), "ΠM(0,2,4,6) · d(5,7)");
+        assert_eq!(from("ΠM(0,2,4,6) · d(5,7)").unwrap(), table);
+        assert_eq!(from("M(0,2,4,6)*d(5,7)").unwrap(), table);
+        assert_eq!(table.canonical_dnf().to_rpn(), "A!B!C&&A!BC&&|");
+
+        assert_eq!(from("Σm()").unwrap().canonical_dnf(), Formula::Const(false));
+        assert_eq!(from("ΠM()").unwrap().canonical_cnf(), Formula::Const(true));
+        assert!(from("Σm(1,8)").is_err());
+        assert!(from("Σm(1,x)").is_err());
+        assert!(from("Σm(1,3").is_err());
+        assert!(from("Σm(1,3) + d(3)").is_err());
+        assert!(from("Σm(1,3) - d(5)").is_err());
+        assert!(from("Σd(1,3)").is_err());
+    }
 }
@@ -0,0 +1,218 @@
+use rsb::formula::Formula;
+use rsb::tseitin::Encoding;
+
+fn tseitin(formula: &str, encoding: Encoding) -> String {
+    match Formula::parse_rpn(formula) {
+        Ok(formula) => formula.tseitin(encoding).cnf.to_rpn(),
+        _ => String::from(formula),
+    }
+}
+
+#[allow(dead_code)]
+fn main() {
+    tseitin("AB&C|", Encoding::Full);
+}
+
+#[cfg(test)]
+mod tseitin {
+    use super::*;
+    use rsb::assignment::Assignment;
+    use rsb::cdcl;
+    use rsb::cnf::Lit;
+    use rsb::evaluator::Evaluator;
+    use rsb::formula::Operator;
+    use rsb::parser::Parser;
+    use rsb::truthtable::TruthTable;
+    use rsb::tseitin::Gate;
+
+    // Checks that the formula holds under an assignment of its variables
+    // exactly when the CNF can be satisfied by extending that assignment
+    fn assert_equisatisfiable(formula: &str, encoding: Encoding) {
+        let formula = Formula::parse_rpn(formula).unwrap();
+        let cnf = formula.tseitin(encoding).cnf.to_formula();
+        let table = TruthTable::from_formula(&formula).unwrap();
+        for row in 0..table.row_count() {
+            let values = table.assignment_of(row).collect::<Vec<_>>();
+            let assignment = Assignment::from_values(&table.variables, &values);
+            let residual = cnf.assign(&assignment);
+            let satisfiable = Evaluator::new(&residual).words().any(|w| w != 0);
+            assert_eq!(satisfiable, table.get(row), "{} under {}", cnf, assignment);
+        }
+    }
+
+    #[test]
+    fn equisatisfiable() {
+        for encoding in [Encoding::Full, Encoding::Polarity] {
+            assert_equisatisfiable("AB&C|", encoding);
+            assert_equisatisfiable("AB^C=", encoding);
+            assert_equisatisfiable("AB>C<!", encoding);
+            assert_equisatisfiable("AB↑C↓D^", encoding);
+            assert_equisatisfiable("AB=!CD^&AC>|", encoding);
+            assert_equisatisfiable("A!B!|!CD&=", encoding);
+            assert_equisatisfiable("A B C D exo/4 B C ^ &", encoding);
+            assert_equisatisfiable("A B C D atleast2/4 ! A D = |", encoding);
+            assert_equisatisfiable("A B C D E maj/5 A B C amo/3 ^", encoding);
+            assert_equisatisfiable("A B C D E atleast3/5 A B C D E exo/5 =", encoding);
+            assert_equisatisfiable("A A B maj/3 A B C amo/3 A B C exo/3 & >", encoding);
+            assert_equisatisfiable("AB&C|AB↑C|&", encoding);
+        }
+    }
+
+    #[test]
+    fn clauses() {
+        assert_eq!(tseitin("A", Encoding::Full), "A");
+        assert_eq!(tseitin("A!", Encoding::Full), "A!");
+        assert_eq!(tseitin("A1&", Encoding::Full), "A");
+        assert_eq!(tseitin("A0&", Encoding::Full), "0");
+        assert_eq!(
+            tseitin("AB&", Encoding::Full),
+            "_t0 ! A | _t0 ! B | _t0 A ! B ! | | _t0 & & &"
+        );
+        assert_eq!(
+            tseitin("AB&", Encoding::Polarity),
+            "_t0 ! A | _t0 ! B | _t0 & &"
+        );
+        assert_eq!(
+            tseitin("AB&!", Encoding::Polarity),
+            "_t0 A ! B ! | | _t0 ! &"
+        );
+        assert_eq!(
+            tseitin("AB↑", Encoding::Polarity),
+            "_t0 A ! B ! | | _t0 ! &"
+        );
+        assert_eq!(
+            tseitin("_t0 _t1 |", Encoding::Polarity),
+            "__t0 ! _t0 _t1 | | __t0 &"
+        );
+    }
+
+    #[test]
+    fn auxiliaries() {
+        let formula = Formula::parse_rpn("AB&C|AB&>").unwrap();
+        let encoded = formula.tseitin(Encoding::Full);
+        assert_eq!(encoded.auxiliaries.len(), 3);
+        assert_eq!(encoded.subformula("_t0"), Some(formula.clone()));
+        assert_eq!(encoded.subformula("_t1").unwrap().to_rpn(), "AB&C|");
+        assert_eq!(encoded.subformula("_t2").unwrap().to_rpn(), "AB&");
+        assert_eq!(encoded.subformula("_t3"), None);
+        // The gates refer to A, B, C and _t0, _t1, _t2 by index
+        let gate = |op, lhs, rhs| Gate {
+            op,
+            lhs: Lit::positive(lhs),
+            rhs: Lit::positive(rhs),
+        };
+        assert_eq!(
+            encoded
+                .auxiliaries
+                .iter()
+                .map(|(_, gate)| *gate)
+                .collect::<Vec<_>>(),
+            [
+                gate(Operator::Imply, 4, 5),
+                gate(Operator::Or, 5, 2),
+                gate(Operator::And, 0, 1)
+            ]
+        );
+
+        // Setting each auxiliary variable to the value of its subformula
+        // satisfies the CNF whenever the formula holds
+        let table = TruthTable::from_formula(&formula).unwrap();
+        for row in table.ones() {
+            let values = table.assignment_of(row).collect::<Vec<_>>();
+            let mut assignment = Assignment::from_values(&table.variables, &values);
+            for (aux, _) in &encoded.auxiliaries {
+                let subformula = encoded.subformula(aux).unwrap();
+                assignment.insert(aux.clone(), subformula.eval(&assignment).unwrap());
+            }
+            assert!(encoded.cnf.to_formula().eval(&assignment).unwrap());
+        }
+    }
+
+    #[test]
+    fn hundreds_of_variables() {
+        let operators = ['&', '|', '^', '>', '=', '↑', '↓', '<'];
+        let rpn = (0..300)
+            .map(|i| match i {
+                0 => String::from("x0"),
+                _ => format!("x{} {}", i, operators[i % operators.len()]),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let formula = Formula::parse_rpn(&rpn).unwrap();
+        let full = formula.tseitin(Encoding::Full);
+        let polarity = formula.tseitin(Encoding::Polarity);
+        assert_eq!(full.auxiliaries.len(), 299);
+        assert_eq!(polarity.auxiliaries.len(), 299);
+        assert!(full.cnf.len() <= 4 * 299 + 1);
+        assert!(polarity.cnf.len() < full.cnf.len());
+        assert_eq!(full.cnf.variables.len(), 599);
+        assert!(full.cnf.iter().all(|clause| clause.len() <= 3));
+    }
+
+    #[test]
+    fn cardinality() {
+        let operands = |n: usize| {
+            (0..n)
+                .map(|i| format!("x{}", i))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let encode = |formula: String| {
+            Formula::parse_rpn(&formula)
+                .unwrap()
+                .tseitin(Encoding::Full)
+                .cnf
+        };
+        // The counters of exactly one grow linearly with the operands
+        let sizes = [100, 200, 400].map(|n| encode(format!("{} exo/{}", operands(n), n)).len());
+        assert_eq!(sizes, [100, 200, 400].map(|n| 9 * n - 8));
+
+        // and those of the majority with the operands times the threshold:
+        // 32 cells for each of the 33 counts, of two gates of three clauses
+        let majority = encode(format!("{} maj/64", operands(64)));
+        assert!(majority.len() <= 6 * 32 * 33 + 1);
+        assert!(majority.iter().all(|clause| clause.len() <= 3));
+        assert!(cdcl::solve(&majority).is_some());
+        let unsatisfiable = encode(format!("{} maj/64 {} amo/64 &", operands(64), operands(64)));
+        assert_eq!(cdcl::solve(&unsatisfiable), None);
+    }
+
+    #[test]
+    fn thousands_of_variables() {
+        // The chain x0 > x1 > ... > x5000 is nested 5000 levels deep, which
+        // the passes over the formula handle without recursing
+        let n = 5000;
+        let rpn = (1..n)
+            .map(|i| format!("x{} x{} > &", i, i + 1))
+            .fold(String::from("x0 x1 >"), |rpn, link| rpn + " " + &link);
+        let chain = Formula::parse_rpn(&rpn).unwrap();
+        // One auxiliary variable per implication and per conjunction,
+        // each defined by a bounded number of clauses
+        let full = chain.tseitin(Encoding::Full);
+        let polarity = chain.tseitin(Encoding::Polarity);
+        assert_eq!(full.auxiliaries.len(), n + n - 1);
+        assert_eq!(full.cnf.variables.len(), n + 1 + 2 * n - 1);
+        assert_eq!(full.cnf.len(), 3 * (2 * n - 1) + 1);
+        assert_eq!(polarity.cnf.len(), 2 * (n - 1) + n + 1);
+
+        // A second copy of the chain shares all its gates
+        let twice = Formula::and(chain.clone(), chain.clone()).tseitin(Encoding::Full);
+        assert_eq!(twice.auxiliaries.len(), full.auxiliaries.len() + 1);
+        let root = twice.auxiliaries[0].1;
+        assert_eq!(root.lhs, root.rhs);
+        assert_eq!(twice.cnf.len(), full.cnf.len() + 3);
+
+        let unsatisfiable = Formula::and(
+            chain,
+            Formula::and(Formula::var("x0"), !Formula::var(&format!("x{}", n))),
+        );
+        let cnf = unsatisfiable.tseitin(Encoding::Polarity).cnf;
+        assert_eq!(cdcl::solve(&cnf), None);
+
+        // as do the other passes
+        let nnf = Parser::new().evaluate_nnf(&rpn).unwrap();
+        assert_eq!(nnf.matches('!').count(), n);
+        let falses = (0..=n).map(|i| (format!("x{}", i), false));
+        assert_eq!(unsatisfiable.eval(&falses.collect()).ok(), Some(false));
+    }
+}
@@ -0,0 +1,103 @@
+use rsb::parser::Parser;
+
+fn core(constraints: &[(&str, &str)]) -> Option<Vec<String>> {
+    let parser = Parser::new();
+    parser.unsatisfiable_core(constraints).unwrap_or(None)
+}
+
+#[allow(dead_code)]
+fn main() {
+    core(&[("a", "AB>"), ("b", "A"), ("c", "B!"), ("d", "C")]);
+}
+
+#[cfg(test)]
+mod unsatisfiable_core {
+    use super::*;
+    use rsb::cdcl::Solver;
+    use rsb::cnf::{Cnf, Lit};
+    use rsb::formula::Formula;
+    use rsb::mus;
+
+    #[test]
+    fn assumptions() {
+        let cnf = Cnf::from_dimacs("p cnf 4 3\n-1 2 0\n-2 3 0\n-3 -4 0\n").unwrap();
+        let mut solver = Solver::new(&cnf);
+        let (a, d) = (Lit::positive(0), Lit::positive(3));
+        assert_eq!(solver.solve_with(&[a, d]), None);
+        let mut failed = solver.failed_assumptions().to_vec();
+        failed.sort();
+        assert_eq!(failed, [a, d]);
+        let model = solver.solve_with(&[a, !d]).unwrap();
+        assert!(model[0] && model[1] && model[2] && !model[3]);
+        assert_eq!(solver.solve_with(&[a, Lit::positive(1), d]), None);
+        assert!(solver.failed_assumptions().contains(&d));
+        // The assumptions do not stay
+        assert!(solver.solve().is_some());
+        assert!(solver.solve_with(&[d, !a]).is_some());
+    }
+
+    #[test]
+    fn minimal() {
+        let names = |names: &[&str]| names.iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            core(&[("a", "AB>"), ("b", "A"), ("c", "B!"), ("d", "C")]),
+            Some(names(&["a", "b", "c"]))
+        );
+        assert_eq!(core(&[("a", "AB|"), ("b", "A!"), ("c", "C")]), None);
+        assert_eq!(core(&[]), None);
+        assert_eq!(
+            core(&[("a", "A"), ("never", "0"), ("b", "A!")]),
+            Some(names(&["never"]))
+        );
+        assert_eq!(
+            core(&[("tautology", "AA!|"), ("x", "AA!&"), ("y", "1")]),
+            Some(names(&["x"]))
+        );
+        // Variables named like the auxiliary and selector variables
+        assert_eq!(
+            core(&[("a", "_t0 _s0 &"), ("b", "_t0 ! _s1 |"), ("c", "_s1 _s0 &")]),
+            None
+        );
+        assert_eq!(
+            core(&[("a", "_t0 _s0 &"), ("b", "_t0 ! _s1 |"), ("c", "_s1 !")]),
+            Some(names(&["a", "b", "c"]))
+        );
+        let parser = Parser::new();
+        assert!(parser
+            .unsatisfiable_core(&[("a", "A"), ("b", "A&")])
+            .is_err());
+    }
+
+    #[test]
+    fn rules() {
+        // A chain x0 > x1 > ... > x29 with x0 and !x29, among unrelated
+        // and redundant rules
+        let mut constraints = (0..29)
+            .map(|i| format!("x{} x{} >", i, i + 1))
+            .collect::<Vec<_>>();
+        constraints.push(String::from("x0 1 &"));
+        constraints.push(String::from("x29 !"));
+        constraints.push(String::from("x0 x29 >"));
+        constraints.push(String::from("y0 y1 |"));
+        constraints.push(String::from("x3 x4 y0 | |"));
+        let formulas = constraints
+            .iter()
+            .map(|c| Formula::parse_rpn(c).unwrap())
+            .collect::<Vec<_>>();
+        let core = mus::minimal_unsatisfiable_subset(&formulas).unwrap();
+        assert!(core == [29, 30, 31] || core == (0..31).collect::<Vec<_>>());
+        // Every constraint of the core is needed
+        for i in 0..core.len() {
+            let mut subset = core.clone();
+            subset.remove(i);
+            let subset = subset
+                .iter()
+                .map(|&i| formulas[i].clone())
+                .collect::<Vec<_>>();
+            assert_eq!(mus::minimal_unsatisfiable_subset(&subset), None);
+        }
+        // Without the shortcut, the whole chain is the only core
+        let core = mus::minimal_unsatisfiable_subset(&formulas[..31]).unwrap();
+        assert_eq!(core, (0..31).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,509 @@
+use crate::cnf::{Cnf, Lit};
+use std::collections::BinaryHeap;
+
+// The number of conflicts of the shortest run between restarts, which is
+// scaled by the Luby sequence 1, 1, 2, 1, 1, 2, 4, ...
+const RESTART_BASE: u64 = 100;
+const VAR_DECAY: f64 = 0.95;
+const CLAUSE_DECAY: f64 = 0.999;
+// The learnt clauses are halved whenever there are more of them than this
+// fraction of the original clauses, plus a constant; the limit grows by
+// LEARNT_GROWTH at every reduction
+const LEARNT_RATIO: f64 = 1.0 / 3.0;
+const LEARNT_MIN: f64 = 1000.0;
+const LEARNT_GROWTH: f64 = 1.1;
+
+/// Counters of the work done by a [`Solver`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    pub decisions: u64,
+    pub propagations: u64,
+    pub conflicts: u64,
+    pub restarts: u64,
+    pub learnt: u64,
+    pub deleted: u64,
+}
+
+struct ClauseData {
+    // The first two literals are the watched ones
+    lits: Vec<Lit>,
+    learnt: bool,
+    activity: f64,
+}
+
+/// A conflict-driven clause-learning SAT solver.
+///
+/// Clauses are visited through two watched literals during unit propagation.
+/// Every conflict is analysed back to its first unique implication point,
+/// giving a clause that is learnt and makes the solver backjump. Branching
+/// picks the unassigned variable of highest VSIDS activity, bumped whenever
+/// a variable takes part in a conflict, with its last value. The search
+/// restarts after a number of conflicts following the Luby sequence, and the
+/// least active half of the learnt clauses is deleted when they grow too
+/// many.
+pub struct Solver {
+    clauses: Vec<ClauseData>,
+    // The number of learnt clauses among the clauses
+    learnts: usize,
+    // The clauses watching each literal, by literal code, which are visited
+    // when the literal becomes false
+    watches: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    levels: Vec<usize>,
+    reasons: Vec<Option<usize>>,
+    phases: Vec<bool>,
+    trail: Vec<Lit>,
+    // The length of the trail at the start of each decision level
+    trail_lim: Vec<usize>,
+    head: usize,
+    activity: Vec<f64>,
+    var_inc: f64,
+    clause_inc: f64,
+    // Variables by activity, with stale entries skipped when popped
+    order: BinaryHeap<(u64, usize)>,
+    max_learnts: f64,
+    // An empty clause was found while adding the clauses
+    unsatisfiable: bool,
+    // The assumptions responsible for the last unsatisfiable answer
+    failed: Vec<Lit>,
+    stats: Stats,
+}
+
+/// The `i`-th term of the Luby sequence, counting from 0.
+fn luby(mut i: u64) -> u64 {
+    let mut size = 1;
+    let mut sequence = 0;
+    while size < i + 1 {
+        sequence += 1;
+        size = 2 * size + 1;
+    }
+    while size - 1 != i {
+        size = (size - 1) >> 1;
+        sequence -= 1;
+        i %= size;
+    }
+    1 << sequence
+}
+
+impl Solver {
+    pub fn new(cnf: &Cnf) -> Self {
+        let len = cnf.variables.len();
+        let mut solver = Solver {
+            clauses: Vec::new(),
+            learnts: 0,
+            watches: vec![Vec::new(); 2 * len],
+            values: vec![None; len],
+            levels: vec![0; len],
+            reasons: vec![None; len],
+            phases: vec![false; len],
+            trail: Vec::new(),
+            trail_lim: Vec::new(),
+            head: 0,
+            activity: vec![0.0; len],
+            var_inc: 1.0,
+            clause_inc: 1.0,
+            order: (0..len).map(|var| (0, var)).collect(),
+            max_learnts: 0.0,
+            unsatisfiable: false,
+            failed: Vec::new(),
+            stats: Stats::default(),
+        };
+        for clause in cnf.iter() {
+            solver.add_clause(clause.lits());
+        }
+        solver.max_learnts = solver.clauses.len() as f64 * LEARNT_RATIO + LEARNT_MIN;
+        solver
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// The assumptions of the last call to [`Solver::solve_with`] that are
+    /// enough to make the CNF unsatisfiable, if it returned `None`. It is
+    /// empty when the CNF is unsatisfiable without assumptions.
+    pub fn failed_assumptions(&self) -> &[Lit] {
+        &self.failed
+    }
+
+    fn value(&self, lit: Lit) -> Option<bool> {
+        self.values[lit.var()].map(|value| lit.apply(value))
+    }
+
+    fn level(&self) -> usize {
+        self.trail_lim.len()
+    }
+
+    /// Adds a clause to the CNF, which may be done between two calls to
+    /// [`Solver::solve`], e.g. to exclude the last model.
+    pub fn add_clause(&mut self, lits: &[Lit]) {
+        let mut lits = lits.to_vec();
+        lits.sort();
+        lits.dedup();
+        if lits.windows(2).any(|pair| pair[1] == !pair[0]) {
+            return;
+        }
+        // Only the assignments of level 0 are left between two searches, so
+        // true literals satisfy the clause for good and false ones can go
+        if lits.iter().any(|&lit| self.value(lit) == Some(true)) {
+            return;
+        }
+        lits.retain(|&lit| self.value(lit).is_none());
+        match lits.len() {
+            0 => self.unsatisfiable = true,
+            1 => match self.value(lits[0]) {
+                Some(false) => self.unsatisfiable = true,
+                Some(true) => (),
+                None => self.enqueue(lits[0], None),
+            },
+            _ => {
+                self.watch(self.clauses.len(), &lits);
+                self.clauses.push(ClauseData {
+                    lits,
+                    learnt: false,
+                    activity: 0.0,
+                });
+            }
+        }
+    }
+
+    fn watch(&mut self, c: usize, lits: &[Lit]) {
+        self.watches[lits[0].code()].push(c);
+        self.watches[lits[1].code()].push(c);
+    }
+
+    fn enqueue(&mut self, lit: Lit, reason: Option<usize>) {
+        let var = lit.var();
+        self.values[var] = Some(lit.is_positive());
+        self.levels[var] = self.level();
+        self.reasons[var] = reason;
+        self.trail.push(lit);
+    }
+
+    // Propagates the literals of the trail not propagated yet, returning the
+    // clause that became false if any
+    fn propagate(&mut self) -> Option<usize> {
+        while self.head < self.trail.len() {
+            let falsified = !self.trail[self.head];
+            self.head += 1;
+            self.stats.propagations += 1;
+            let watchers = std::mem::take(&mut self.watches[falsified.code()]);
+            let mut kept = Vec::with_capacity(watchers.len());
+            let mut conflict = None;
+            for (i, &c) in watchers.iter().enumerate() {
+                let lits = &mut self.clauses[c].lits;
+                if lits[0] == falsified {
+                    lits.swap(0, 1);
+                }
+                let first = lits[0];
+                if self.values[first.var()].map(|v| first.apply(v)) == Some(true) {
+                    kept.push(c);
+                    continue;
+                }
+                let replacement = (2..lits.len()).find(|&k| {
+                    let lit = lits[k];
+                    self.values[lit.var()].map(|v| lit.apply(v)) != Some(false)
+                });
+                if let Some(k) = replacement {
+                    lits.swap(1, k);
+                    let watched = lits[1];
+                    self.watches[watched.code()].push(c);
+                    continue;
+                }
+                kept.push(c);
+                if self.value(first) == Some(false) {
+                    kept.extend_from_slice(&watchers[i + 1..]);
+                    conflict = Some(c);
+                    break;
+                }
+                self.enqueue(first, Some(c));
+            }
+            self.watches[falsified.code()] = kept;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    fn bump_variable(&mut self, var: usize) {
+        self.activity[var] += self.var_inc;
+        if self.activity[var] > 1e100 {
+            for activity in &mut self.activity {
+                *activity *= 1e-100;
+            }
+            self.var_inc *= 1e-100;
+            self.rebuild_order();
+        }
+        if self.values[var].is_none() {
+            self.order.push((self.activity[var].to_bits(), var));
+        }
+    }
+
+    fn bump_clause(&mut self, c: usize) {
+        self.clauses[c].activity += self.clause_inc;
+        if self.clauses[c].activity > 1e20 {
+            for clause in self.clauses.iter_mut().filter(|clause| clause.learnt) {
+                clause.activity *= 1e-20;
+            }
+            self.clause_inc *= 1e-20;
+        }
+    }
+
+    // The clause learnt from a conflict, with its first unique implication
+    // point first and a literal of the level to backjump to second, and
+    // that level
+    fn analyze(&mut self, conflict: usize) -> (Vec<Lit>, usize) {
+        let mut seen = vec![false; self.values.len()];
+        let mut learnt = vec![Lit::positive(0)];
+        let mut pending = 0;
+        let mut index = self.trail.len();
+        let mut reason = Some(conflict);
+        let mut uip = None;
+        while let Some(c) = reason {
+            if self.clauses[c].learnt {
+                self.bump_clause(c);
+            }
+            // The literal implied by the reason clause is skipped
+            let skip = usize::from(uip.is_some());
+            for k in skip..self.clauses[c].lits.len() {
+                let lit = self.clauses[c].lits[k];
+                let var = lit.var();
+                if seen[var] || self.levels[var] == 0 {
+                    continue;
+                }
+                seen[var] = true;
+                self.bump_variable(var);
+                if self.levels[var] == self.level() {
+                    pending += 1;
+                } else {
+                    learnt.push(lit);
+                }
+            }
+            // The next literal of the current level on the trail
+            let lit = loop {
+                index -= 1;
+                if seen[self.trail[index].var()] {
+                    break self.trail[index];
+                }
+            };
+            seen[lit.var()] = false;
+            pending -= 1;
+            uip = Some(lit);
+            reason = match pending {
+                0 => None,
+                _ => self.reasons[lit.var()],
+            };
+        }
+        learnt[0] = !uip.expect("the conflict is at a decision level");
+        let mut backjump = 0;
+        if learnt.len() > 1 {
+            let (k, level) = (1..learnt.len())
+                .map(|k| (k, self.levels[learnt[k].var()]))
+                .max_by_key(|&(_, level)| level)
+                .unwrap();
+            learnt.swap(1, k);
+            backjump = level;
+        }
+        (learnt, backjump)
+    }
+
+    // The assumptions, including `assumption` itself, from which its
+    // negation was propagated
+    fn analyze_final(&self, assumption: Lit) -> Vec<Lit> {
+        let mut failed = vec![assumption];
+        if self.level() == 0 {
+            return failed;
+        }
+        let mut seen = vec![false; self.values.len()];
+        seen[assumption.var()] = true;
+        for k in (self.trail_lim[0]..self.trail.len()).rev() {
+            let lit = self.trail[k];
+            if !seen[lit.var()] {
+                continue;
+            }
+            match self.reasons[lit.var()] {
+                // Every decision so far is an assumption
+                None => failed.push(lit),
+                Some(c) => {
+                    for other in &self.clauses[c].lits[1..] {
+                        if self.levels[other.var()] > 0 {
+                            seen[other.var()] = true;
+                        }
+                    }
+                }
+            }
+        }
+        failed
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        if self.level() <= level {
+            return;
+        }
+        let len = self.trail_lim[level];
+        for k in (len..self.trail.len()).rev() {
+            let var = self.trail[k].var();
+            self.phases[var] = self.trail[k].is_positive();
+            self.values[var] = None;
+            self.reasons[var] = None;
+            self.order.push((self.activity[var].to_bits(), var));
+        }
+        self.trail.truncate(len);
+        self.trail_lim.truncate(level);
+        self.head = len;
+    }
+
+    // Every unassigned variable has an entry with its current activity in
+    // the heap, so the first valid entry is the most active variable
+    fn decide(&mut self) -> Option<Lit> {
+        while let Some((activity, var)) = self.order.pop() {
+            if self.values[var].is_none() && activity == self.activity[var].to_bits() {
+                return Some(Lit::new(var, self.phases[var]));
+            }
+        }
+        None
+    }
+
+    fn rebuild_order(&mut self) {
+        self.order = (0..self.activity.len())
+            .filter(|&var| self.values[var].is_none())
+            .map(|var| (self.activity[var].to_bits(), var))
+            .collect();
+    }
+
+    // Deletes the least active half of the learnt clauses, at level 0 where
+    // no learnt clause is the reason of an assignment
+    fn reduce(&mut self) {
+        let mut learnt = (0..self.clauses.len())
+            .filter(|&c| self.clauses[c].learnt && self.clauses[c].lits.len() > 2)
+            .collect::<Vec<usize>>();
+        learnt.sort_by(|&a, &b| {
+            self.clauses[a]
+                .activity
+                .total_cmp(&self.clauses[b].activity)
+        });
+        let mut deleted = vec![false; self.clauses.len()];
+        for &c in &learnt[..learnt.len() / 2] {
+            deleted[c] = true;
+        }
+        self.stats.deleted += (learnt.len() / 2) as u64;
+        self.learnts -= learnt.len() / 2;
+        let mut k = 0;
+        self.clauses.retain(|_| {
+            k += 1;
+            !deleted[k - 1]
+        });
+        for reason in &mut self.reasons {
+            *reason = None;
+        }
+        for watches in &mut self.watches {
+            watches.clear();
+        }
+        for c in 0..self.clauses.len() {
+            let lits = std::mem::take(&mut self.clauses[c].lits);
+            self.watch(c, &lits);
+            self.clauses[c].lits = lits;
+        }
+    }
+
+    /// A satisfying assignment of the CNF, variable `v` having the value
+    /// `model[v]`, or `None` if it is unsatisfiable.
+    pub fn solve(&mut self) -> Option<Vec<bool>> {
+        self.solve_with(&[])
+    }
+
+    /// A satisfying assignment of the CNF in which every assumption is
+    /// true, or `None` if there is none, in which case
+    /// [`Solver::failed_assumptions`] tells which assumptions conflict. The
+    /// assumptions only hold for this call, while the learnt clauses are kept
+    /// for the next ones.
+    pub fn solve_with(&mut self, assumptions: &[Lit]) -> Option<Vec<bool>> {
+        self.failed.clear();
+        if self.unsatisfiable {
+            return None;
+        }
+        let mut restarts = 0;
+        let mut budget = RESTART_BASE * luby(restarts);
+        loop {
+            if let Some(conflict) = self.propagate() {
+                self.stats.conflicts += 1;
+                if self.level() == 0 {
+                    self.unsatisfiable = true;
+                    return None;
+                }
+                let (learnt, level) = self.analyze(conflict);
+                self.backtrack(level);
+                if learnt.len() == 1 {
+                    self.enqueue(learnt[0], None);
+                } else {
+                    let c = self.clauses.len();
+                    self.watch(c, &learnt);
+                    self.clauses.push(ClauseData {
+                        lits: learnt.clone(),
+                        learnt: true,
+                        activity: 0.0,
+                    });
+                    self.learnts += 1;
+                    self.bump_clause(c);
+                    self.enqueue(learnt[0], Some(c));
+                }
+                self.stats.learnt += 1;
+                self.var_inc /= VAR_DECAY;
+                self.clause_inc /= CLAUSE_DECAY;
+                budget = budget.saturating_sub(1);
+                continue;
+            }
+            if budget == 0 {
+                self.backtrack(0);
+                restarts += 1;
+                self.stats.restarts += 1;
+                budget = RESTART_BASE * luby(restarts);
+                self.rebuild_order();
+                if self.learnts as f64 > self.max_learnts {
+                    self.reduce();
+                    self.max_learnts *= LEARNT_GROWTH;
+                }
+                continue;
+            }
+            // The first decision levels are those of the assumptions
+            if let Some(&assumption) = assumptions.get(self.level()) {
+                match self.value(assumption) {
+                    Some(true) => self.trail_lim.push(self.trail.len()),
+                    Some(false) => {
+                        self.failed = self.analyze_final(assumption);
+                        self.backtrack(0);
+                        return None;
+                    }
+                    None => {
+                        self.trail_lim.push(self.trail.len());
+                        self.enqueue(assumption, None);
+                    }
+                }
+                continue;
+            }
+            match self.decide() {
+                Some(lit) => {
+                    self.stats.decisions += 1;
+                    self.trail_lim.push(self.trail.len());
+                    self.enqueue(lit, None);
+                }
+                None => {
+                    let model = self.values.iter().map(|v| v.unwrap()).collect();
+                    self.backtrack(0);
+                    return Some(model);
+                }
+            }
+        }
+    }
+}
+
+/// A satisfying assignment of the CNF found by a [`Solver`], variable `v`
+/// having the value `model[v]`, or `None` if it is unsatisfiable.
+pub fn solve(cnf: &Cnf) -> Option<Vec<bool>> {
+    Solver::new(cnf).solve()
+}
+
+pub fn is_satisfiable(cnf: &Cnf) -> bool {
+    solve(cnf).is_some()
+}
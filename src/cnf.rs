@@ -0,0 +1,217 @@
+use crate::formula::{join_rpn, Formula};
+use std::fmt;
+use std::ops::Not;
+
+/// A variable or its negation, as the index of the variable in
+/// [`Cnf::variables`] shifted left once, with the lowest bit set when the
+/// literal is negated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Lit(u32);
+
+impl Lit {
+    pub fn new(var: usize, positive: bool) -> Self {
+        Lit((var as u32) << 1 | !positive as u32)
+    }
+
+    pub fn positive(var: usize) -> Self {
+        Lit::new(var, true)
+    }
+
+    pub fn negative(var: usize) -> Self {
+        Lit::new(var, false)
+    }
+
+    pub fn var(self) -> usize {
+        (self.0 >> 1) as usize
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 & 1 == 0
+    }
+
+    /// A dense index of the literal, `2 * var` when positive and
+    /// `2 * var + 1` when negated.
+    pub fn code(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn from_code(code: usize) -> Self {
+        Lit(code as u32)
+    }
+
+    /// The value of the literal when its variable is `value`.
+    pub fn apply(self, value: bool) -> bool {
+        value == self.is_positive()
+    }
+}
+
+impl Not for Lit {
+    type Output = Lit;
+
+    fn not(self) -> Lit {
+        Lit(self.0 ^ 1)
+    }
+}
+
+/// A disjunction of literals. The empty clause is false.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Clause(Vec<Lit>);
+
+impl Clause {
+    pub fn new(lits: Vec<Lit>) -> Self {
+        Clause(lits)
+    }
+
+    pub fn lits(&self) -> &[Lit] {
+        &self.0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Lit> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, lit: Lit) -> bool {
+        self.0.contains(&lit)
+    }
+
+    /// Whether the clause contains a variable and its negation.
+    pub fn is_tautology(&self) -> bool {
+        self.0.iter().any(|&lit| self.contains(!lit))
+    }
+}
+
+impl FromIterator<Lit> for Clause {
+    fn from_iter<I: IntoIterator<Item = Lit>>(iter: I) -> Self {
+        Clause(iter.into_iter().collect())
+    }
+}
+
+/// A formula in conjunctive normal form over named variables. The empty
+/// conjunction is true.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Cnf {
+    pub variables: Vec<String>,
+    pub clauses: Vec<Clause>,
+}
+
+impl Cnf {
+    pub fn new(variables: Vec<String>) -> Self {
+        Cnf {
+            variables,
+            clauses: Vec::new(),
+        }
+    }
+
+    /// The CNF of a constant: no clause for true, the empty clause for false.
+    pub fn constant(value: bool) -> Self {
+        Cnf {
+            variables: Vec::new(),
+            clauses: if value {
+                vec![]
+            } else {
+                vec![Clause::default()]
+            },
+        }
+    }
+
+    pub fn push(&mut self, clause: Clause) {
+        self.clauses.push(clause);
+    }
+
+    /// The index of the variable `name`.
+    pub fn variable(&self, name: &str) -> Option<usize> {
+        self.variables.iter().position(|v| v == name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.clauses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Clause> {
+        self.clauses.iter()
+    }
+
+    /// The value of the CNF when variable `v` has the value `values[v]`.
+    pub fn evaluate(&self, values: &[bool]) -> bool {
+        self.clauses
+            .iter()
+            .all(|clause| clause.iter().any(|lit| lit.apply(values[lit.var()])))
+    }
+
+    fn literal(&self, lit: Lit) -> Formula {
+        let var = Formula::var(&self.variables[lit.var()]);
+        match lit.is_positive() {
+            true => var,
+            false => !var,
+        }
+    }
+
+    pub fn to_formula(&self) -> Formula {
+        Formula::conjunction(
+            self.clauses
+                .iter()
+                .map(|clause| Formula::disjunction(clause.iter().map(|lit| self.literal(lit)))),
+        )
+    }
+
+    /// The CNF in reverse polish notation, with the clauses and their
+    /// literals in order and the operators at the end of each clause and of
+    /// the formula, as [`Formula::to_rpn`] prints [`Cnf::to_formula`].
+    pub fn to_rpn(&self) -> String {
+        let mut tokens = Vec::new();
+        for clause in &self.clauses {
+            match clause.is_empty() {
+                true => tokens.push("0"),
+                false => {
+                    for lit in clause.iter() {
+                        tokens.push(&self.variables[lit.var()]);
+                        if !lit.is_positive() {
+                            tokens.push("!");
+                        }
+                    }
+                    tokens.extend(std::iter::repeat_n("|", clause.len() - 1));
+                }
+            }
+        }
+        match self.clauses.len() {
+            0 => tokens.push("1"),
+            n => tokens.extend(std::iter::repeat_n("&", n - 1)),
+        }
+        join_rpn(&tokens)
+    }
+}
+
+impl fmt::Display for Cnf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.clauses.is_empty() {
+            return write!(f, "1");
+        }
+        for (i, clause) in self.clauses.iter().enumerate() {
+            if i > 0 {
+                write!(f, " & ")?;
+            }
+            let lits = clause
+                .iter()
+                .map(|lit| self.literal(lit).to_string())
+                .collect::<Vec<_>>();
+            match lits.len() {
+                0 => write!(f, "0")?,
+                1 => write!(f, "{}", lits[0])?,
+                _ => write!(f, "({})", lits.join(" | "))?,
+            }
+        }
+        Ok(())
+    }
+}
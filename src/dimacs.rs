@@ -0,0 +1,158 @@
+use crate::cnf::{Clause, Cnf, Lit};
+use crate::error::{DimacsError, DimacsErrorKind};
+use crate::formula::identifier_length;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+// Comment lines of the form `c var <index> <name>` give the name of a variable
+const NAME_COMMENT: &str = "var";
+
+#[derive(Clone, Copy)]
+struct Reader<'a> {
+    line: usize,
+    content: &'a str,
+}
+
+impl Reader<'_> {
+    fn error(&self, kind: DimacsErrorKind) -> DimacsError {
+        DimacsError {
+            kind,
+            line: self.line,
+            content: String::from(self.content),
+        }
+    }
+}
+
+impl Cnf {
+    /// The CNF in the DIMACS `p cnf` format, variable `i` of the CNF being
+    /// numbered `i + 1`, preceded by one `c var <number> <name>` comment
+    /// line per variable.
+    pub fn to_dimacs(&self) -> String {
+        let mut dimacs = String::new();
+        for (i, name) in self.variables.iter().enumerate() {
+            writeln!(dimacs, "c {} {} {}", NAME_COMMENT, i + 1, name).unwrap();
+        }
+        writeln!(dimacs, "p cnf {} {}", self.variables.len(), self.len()).unwrap();
+        for clause in self.iter() {
+            for lit in clause.iter() {
+                let number = lit.var() as i64 + 1;
+                write!(
+                    dimacs,
+                    "{} ",
+                    if lit.is_positive() { number } else { -number }
+                )
+                .unwrap();
+            }
+            dimacs.push_str("0\n");
+        }
+        dimacs
+    }
+
+    /// Reads a CNF in the DIMACS `p cnf` format.
+    ///
+    /// The variables are named after the `c var <number> <name>` comments
+    /// written by [`Cnf::to_dimacs`], and `x<number>` when there is none.
+    /// Clauses may span several lines, and a line starting with `%` ends the
+    /// file as in the SATLIB benchmarks.
+    pub fn from_dimacs(input: &str) -> Result<Cnf, DimacsError> {
+        let mut names: Vec<Option<String>> = Vec::new();
+        let mut named: Vec<(Reader, usize, &str)> = Vec::new();
+        let mut header: Option<(usize, usize)> = None;
+        let mut clauses = Vec::new();
+        let mut clause = Vec::new();
+        let mut last = Reader {
+            line: 0,
+            content: "",
+        };
+        for (i, content) in input.lines().enumerate() {
+            let reader = Reader {
+                line: i + 1,
+                content,
+            };
+            let mut tokens = content.split_whitespace();
+            match tokens.next() {
+                None => continue,
+                Some("%") => break,
+                Some("c") => {
+                    if let (Some(NAME_COMMENT), Some(number), Some(name), None) =
+                        (tokens.next(), tokens.next(), tokens.next(), tokens.next())
+                    {
+                        if let Ok(number) = number.parse::<usize>() {
+                            named.push((reader, number, name));
+                        }
+                    }
+                    continue;
+                }
+                Some("p") => {
+                    let counts = match (tokens.next(), tokens.next(), tokens.next(), tokens.next())
+                    {
+                        (Some("cnf"), Some(variables), Some(clauses), None) => {
+                            variables.parse().ok().zip(clauses.parse().ok())
+                        }
+                        _ => None,
+                    };
+                    if header.is_some() || counts.is_none() {
+                        return Err(reader.error(DimacsErrorKind::InvalidHeader));
+                    }
+                    header = counts;
+                    names = vec![None; header.unwrap().0];
+                    continue;
+                }
+                Some(_) => (),
+            }
+            let Some((variables, _)) = header else {
+                return Err(reader.error(DimacsErrorKind::MissingHeader));
+            };
+            for token in content.split_whitespace() {
+                let number = token
+                    .parse::<i64>()
+                    .map_err(|_| reader.error(DimacsErrorKind::InvalidLiteral))?;
+                if number == 0 {
+                    clauses.push(Clause::new(std::mem::take(&mut clause)));
+                } else if number.unsigned_abs() as usize > variables {
+                    return Err(reader.error(DimacsErrorKind::VariableOutOfRange));
+                } else {
+                    clause.push(Lit::new(number.unsigned_abs() as usize - 1, number > 0));
+                }
+            }
+            last = reader;
+        }
+        let Some((variables, count)) = header else {
+            return Err(last.error(DimacsErrorKind::MissingHeader));
+        };
+        // The last clause may lack its terminating 0
+        if !clause.is_empty() {
+            clauses.push(Clause::new(clause));
+        }
+        if clauses.len() != count {
+            return Err(last.error(DimacsErrorKind::ClauseCountMismatch));
+        }
+        // The comment naming each variable, if any
+        let mut comments = vec![None; variables];
+        for (reader, number, name) in named {
+            if number == 0 || number > variables {
+                return Err(reader.error(DimacsErrorKind::VariableOutOfRange));
+            }
+            if identifier_length(name) != name.len() {
+                return Err(reader.error(DimacsErrorKind::InvalidName));
+            }
+            names[number - 1] = Some(String::from(name));
+            comments[number - 1] = Some(reader);
+        }
+        let variables = names
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| name.unwrap_or_else(|| format!("x{}", i + 1)))
+            .collect::<Vec<String>>();
+        let mut seen = HashMap::new();
+        for (i, name) in variables.iter().enumerate() {
+            if let Some(first) = seen.insert(name, i) {
+                // Default names are distinct, so one of the two comes from a
+                // comment
+                let reader = comments[i].or(comments[first]).unwrap_or(last);
+                return Err(reader.error(DimacsErrorKind::InvalidName));
+            }
+        }
+        Ok(Cnf { variables, clauses })
+    }
+}
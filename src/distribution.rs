@@ -0,0 +1,107 @@
+use crate::cnf::{Clause, Cnf, Lit};
+use crate::error::TooManyClauses;
+use crate::formula::Formula;
+use std::collections::{BTreeSet, HashMap};
+
+// The literals of a clause, as a set so that merging two clauses is a union
+type LitSet = BTreeSet<Lit>;
+
+struct Distributor {
+    indices: HashMap<String, usize>,
+    max_clauses: usize,
+}
+
+fn is_tautology(clause: &LitSet) -> bool {
+    clause.iter().any(|&lit| clause.contains(&!lit))
+}
+
+// Drops the clauses that contain another clause of the list, keeping the
+// rest in lexicographic order
+fn remove_subsumed(mut clauses: Vec<LitSet>) -> Vec<LitSet> {
+    clauses.sort_by_key(|clause| clause.len());
+    let mut kept: Vec<LitSet> = Vec::new();
+    for clause in clauses {
+        if !kept.iter().any(|other| other.is_subset(&clause)) {
+            kept.push(clause);
+        }
+    }
+    kept.sort();
+    kept
+}
+
+impl Distributor {
+    fn check(&self, count: usize) -> Result<(), TooManyClauses> {
+        match count > self.max_clauses {
+            true => Err(TooManyClauses {
+                limit: self.max_clauses,
+            }),
+            false => Ok(()),
+        }
+    }
+
+    // The clauses of a formula in negation normal form, from the leaves up
+    fn clauses(&self, formula: &Formula) -> Result<Vec<LitSet>, TooManyClauses> {
+        formula.fold(|node, children: Vec<Result<Vec<LitSet>, TooManyClauses>>| {
+            let mut children = children.into_iter().collect::<Result<Vec<_>, _>>()?;
+            let clauses = match node {
+                Formula::Const(true) => Vec::new(),
+                Formula::Const(false) => vec![LitSet::new()],
+                Formula::Var(name) => vec![LitSet::from([Lit::positive(self.indices[name])])],
+                Formula::Not(child) => match &**child {
+                    Formula::Var(name) => {
+                        vec![LitSet::from([Lit::negative(self.indices[name])])]
+                    }
+                    _ => unreachable!("the formula is in negation normal form"),
+                },
+                Formula::And(..) => {
+                    let rhs = children.pop().unwrap();
+                    let mut clauses = children.pop().unwrap();
+                    clauses.extend(rhs);
+                    self.check(clauses.len())?;
+                    clauses
+                }
+                Formula::Or(..) => {
+                    let mut product = BTreeSet::new();
+                    for a in &children[0] {
+                        for b in &children[1] {
+                            let clause = a | b;
+                            if !is_tautology(&clause) {
+                                product.insert(clause);
+                                self.check(product.len())?;
+                            }
+                        }
+                    }
+                    product.into_iter().collect()
+                }
+                _ => unreachable!("the formula is in negation normal form"),
+            };
+            Ok(remove_subsumed(clauses))
+        })
+    }
+}
+
+impl Formula {
+    /// An equivalent CNF, obtained by distributing the disjunctions of the
+    /// negation normal form over its conjunctions.
+    ///
+    /// Tautological clauses and clauses containing another clause are dropped
+    /// along the way. As the number of clauses can grow exponentially, the
+    /// conversion fails as soon as an intermediate CNF has more than
+    /// `max_clauses` clauses.
+    pub fn distribute_cnf(&self, max_clauses: usize) -> Result<Cnf, TooManyClauses> {
+        let variables = self.variables();
+        let distributor = Distributor {
+            indices: variables
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.clone(), i))
+                .collect(),
+            max_clauses,
+        };
+        let clauses = distributor.clauses(&self.to_nnf())?;
+        Ok(Cnf {
+            variables,
+            clauses: clauses.into_iter().map(Clause::from_iter).collect(),
+        })
+    }
+}
@@ -0,0 +1,171 @@
+use crate::cnf::{Cnf, Lit};
+
+/// A DPLL solver: it assigns the literals implied by unit clauses and the
+/// pure literals, then branches on a variable of the shortest open clause
+/// and backtracks chronologically on conflicts.
+struct Dpll<'a> {
+    cnf: &'a Cnf,
+    values: Vec<Option<bool>>,
+    trail: Vec<Lit>,
+    // The first literal of the trail whose consequences were not propagated yet
+    head: usize,
+    // The clauses containing each literal, by literal code
+    occurrences: Vec<Vec<usize>>,
+}
+
+impl Dpll<'_> {
+    fn value(&self, lit: Lit) -> Option<bool> {
+        self.values[lit.var()].map(|value| lit.apply(value))
+    }
+
+    fn assign(&mut self, lit: Lit) {
+        self.values[lit.var()] = Some(lit.is_positive());
+        self.trail.push(lit);
+    }
+
+    fn backtrack(&mut self, len: usize) {
+        for lit in self.trail.drain(len..) {
+            self.values[lit.var()] = None;
+        }
+        self.head = len;
+    }
+
+    // Assigns the last literal of every clause whose other literals are
+    // false, returning false on a clause with only false literals
+    fn propagate(&mut self) -> bool {
+        while self.head < self.trail.len() {
+            let falsified = !self.trail[self.head];
+            self.head += 1;
+            for i in 0..self.occurrences[falsified.code()].len() {
+                let c = self.occurrences[falsified.code()][i];
+                let mut unassigned = None;
+                let mut open = 0;
+                let mut satisfied = false;
+                for lit in self.cnf.clauses[c].iter() {
+                    match self.value(lit) {
+                        Some(true) => {
+                            satisfied = true;
+                            break;
+                        }
+                        Some(false) => (),
+                        None => {
+                            unassigned = Some(lit);
+                            open += 1;
+                        }
+                    }
+                }
+                match (satisfied, open) {
+                    (true, _) => (),
+                    (false, 0) => return false,
+                    (false, 1) => self.assign(unassigned.unwrap()),
+                    _ => (),
+                }
+            }
+        }
+        true
+    }
+
+    // Assigns the literals whose negation occurs in no open clause, and
+    // returns a literal of a shortest open clause to branch on, if any
+    fn eliminate_pure(&mut self) -> Option<Lit> {
+        let mut occurs = vec![false; 2 * self.values.len()];
+        let mut branch: Option<(usize, Lit)> = None;
+        for clause in self.cnf.iter() {
+            if clause.iter().any(|lit| self.value(lit) == Some(true)) {
+                continue;
+            }
+            let open = clause
+                .lits()
+                .iter()
+                .copied()
+                .filter(|&lit| self.value(lit).is_none());
+            for lit in open.clone() {
+                occurs[lit.code()] = true;
+            }
+            let len = open.clone().count();
+            if branch.is_none_or(|(shortest, _)| len < shortest) {
+                branch = open.clone().next().map(|lit| (len, lit));
+            }
+        }
+        for var in 0..self.values.len() {
+            let (positive, negative) = (Lit::positive(var), Lit::negative(var));
+            match (occurs[positive.code()], occurs[negative.code()]) {
+                (true, false) => self.assign(positive),
+                (false, true) => self.assign(negative),
+                _ => (),
+            }
+        }
+        branch
+            .map(|(_, lit)| lit)
+            .filter(|lit| self.value(*lit).is_none())
+    }
+
+    fn solve(&mut self) -> Option<Vec<bool>> {
+        for clause in self.cnf.iter() {
+            match clause.lits() {
+                [] => return None,
+                [lit] => match self.value(*lit) {
+                    Some(false) => return None,
+                    Some(true) => (),
+                    None => self.assign(*lit),
+                },
+                _ => (),
+            }
+        }
+        // The trail length before each decision, the decision and whether
+        // it is already the second branch
+        let mut decisions: Vec<(usize, Lit, bool)> = Vec::new();
+        loop {
+            if !self.propagate() {
+                loop {
+                    let (len, lit, flipped) = decisions.pop()?;
+                    self.backtrack(len);
+                    if !flipped {
+                        decisions.push((len, !lit, true));
+                        self.assign(!lit);
+                        break;
+                    }
+                }
+                continue;
+            }
+            match self.eliminate_pure() {
+                Some(lit) => {
+                    decisions.push((self.trail.len(), lit, false));
+                    self.assign(lit);
+                }
+                None if self.head == self.trail.len() => break,
+                // Pure literals were assigned, which only satisfy clauses
+                None => self.head = self.trail.len(),
+            }
+        }
+        Some(
+            self.values
+                .iter()
+                .map(|value| value.unwrap_or(false))
+                .collect(),
+        )
+    }
+}
+
+/// A satisfying assignment of the CNF, variable `v` having the value
+/// `model[v]`, or `None` if the CNF is unsatisfiable.
+pub fn solve(cnf: &Cnf) -> Option<Vec<bool>> {
+    let mut occurrences = vec![Vec::new(); 2 * cnf.variables.len()];
+    for (c, clause) in cnf.iter().enumerate() {
+        for lit in clause.iter() {
+            occurrences[lit.code()].push(c);
+        }
+    }
+    Dpll {
+        cnf,
+        values: vec![None; cnf.variables.len()],
+        trail: Vec::new(),
+        head: 0,
+        occurrences,
+    }
+    .solve()
+}
+
+pub fn is_satisfiable(cnf: &Cnf) -> bool {
+    solve(cnf).is_some()
+}
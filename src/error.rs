@@ -0,0 +1,136 @@
+use crate::formula::Formula;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    UnexpectedCharacter,
+    UnexpectedToken,
+    MissingOperand,
+    TrailingOperands,
+    UnexpectedEnd,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub input: String,
+    pub offset: usize,
+    pub found: Option<char>,
+    pub depth: usize,
+    pub expected: &'static str,
+}
+
+impl ParseError {
+    pub fn new(
+        kind: ParseErrorKind,
+        input: &str,
+        offset: usize,
+        depth: usize,
+        expected: &'static str,
+    ) -> Self {
+        ParseError {
+            kind,
+            input: String::from(input),
+            offset,
+            found: input[offset..].chars().next(),
+            depth,
+            expected,
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let column = self.input[..self.offset].chars().count();
+        format!("{}\n{}^", self.input, " ".repeat(column))
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.kind, self.found) {
+            (ParseErrorKind::MissingOperand, Some(c)) => write!(f, "missing operand for '{}'", c)?,
+            (ParseErrorKind::TrailingOperands, _) => {
+                write!(f, "{} operands left without an operator", self.depth)?
+            }
+            (_, Some(c)) => write!(f, "unexpected '{}'", c)?,
+            (_, None) => write!(f, "unexpected end of formula")?,
+        }
+        writeln!(
+            f,
+            " at offset {} (depth {}), expected {}",
+            self.offset, self.depth, self.expected
+        )?;
+        write!(f, "{}", self.render())
+    }
+}
+
+impl Error for ParseError {}
+
+/// A formula that could not be evaluated because its value depends on
+/// variables missing from the assignment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnassignedVariables {
+    pub residual: Formula,
+}
+
+impl fmt::Display for UnassignedVariables {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Unassigned variables {}, residual formula '{}'",
+            self.residual.variables().join(", "),
+            self.residual
+        )
+    }
+}
+
+impl Error for UnassignedVariables {}
+
+/// A conversion to CNF that was stopped because it needed more clauses than
+/// allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooManyClauses {
+    pub limit: usize,
+}
+
+impl fmt::Display for TooManyClauses {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CNF would have more than {} clauses", self.limit)
+    }
+}
+
+impl Error for TooManyClauses {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DimacsErrorKind {
+    MissingHeader,
+    InvalidHeader,
+    InvalidLiteral,
+    VariableOutOfRange,
+    InvalidName,
+    ClauseCountMismatch,
+}
+
+/// An error in a DIMACS CNF file, at a line counted from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DimacsError {
+    pub kind: DimacsErrorKind,
+    pub line: usize,
+    pub content: String,
+}
+
+impl fmt::Display for DimacsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self.kind {
+            DimacsErrorKind::MissingHeader => "expected a 'p cnf <variables> <clauses>' line first",
+            DimacsErrorKind::InvalidHeader => "invalid 'p cnf <variables> <clauses>' line",
+            DimacsErrorKind::InvalidLiteral => "invalid literal",
+            DimacsErrorKind::VariableOutOfRange => "variable not declared in the header",
+            DimacsErrorKind::InvalidName => "invalid or duplicate variable name",
+            DimacsErrorKind::ClauseCountMismatch => "number of clauses differs from the header",
+        };
+        write!(f, "{} at line {}: '{}'", message, self.line, self.content)
+    }
+}
+
+impl Error for DimacsError {}
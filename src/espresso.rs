@@ -0,0 +1,198 @@
+use crate::quinemccluskey::Implicant;
+use crate::truthtable::{Output, TruthTable};
+
+// The expand, irredundant and reduce passes stop after this many rounds
+// even if the cover keeps improving
+const MAX_ROUNDS: usize = 16;
+
+/// A heuristic minimiser in the style of Espresso, for functions too large
+/// for an exact cover.
+///
+/// The cover is kept as a list of cubes together with the number of cubes
+/// covering each row, so every pass is linear in the size of the cubes it
+/// touches rather than in the number of cubes. The counts take one entry per
+/// row, so the minimiser is limited to the functions whose truth table fits
+/// in memory, like the exact methods. The cubes must cover the
+/// required rows and may cover the other valid ones, i.e. the don't cares.
+struct Espresso<'a> {
+    len: usize,
+    required: &'a dyn Fn(usize) -> bool,
+    valid: &'a dyn Fn(usize) -> bool,
+    cover: Vec<Implicant>,
+    counts: Vec<u32>,
+}
+
+impl Espresso<'_> {
+    fn add(&mut self, cube: Implicant) {
+        for row in cube.rows() {
+            self.counts[row] += 1;
+        }
+        self.cover.push(cube);
+    }
+
+    fn remove(&mut self, i: usize) -> Implicant {
+        let cube = self.cover.swap_remove(i);
+        for row in cube.rows() {
+            self.counts[row] -= 1;
+        }
+        cube
+    }
+
+    // Raises the fixed variables of `cube` one at a time, starting at `offset`,
+    // as long as the cube stays within the valid rows
+    fn expand_cube(&self, mut cube: Implicant, offset: usize) -> Implicant {
+        for b in 0..self.len {
+            let bit = 1 << ((b + offset) % self.len);
+            if cube.mask & bit != 0 {
+                continue;
+            }
+            let mirror = Implicant {
+                value: cube.value ^ bit,
+                mask: cube.mask,
+            };
+            if mirror.rows().all(|row| (self.valid)(row)) {
+                cube = Implicant {
+                    value: cube.value & !bit,
+                    mask: cube.mask | bit,
+                };
+            }
+        }
+        cube
+    }
+
+    fn expand(&mut self, offset: usize) {
+        let mut cubes = std::mem::take(&mut self.cover);
+        cubes.sort_by_key(|cube| std::cmp::Reverse(cube.size()));
+        for cube in &cubes {
+            for row in cube.rows() {
+                self.counts[row] -= 1;
+            }
+        }
+        for cube in cubes {
+            if self.required_rows(cube).all(|row| self.counts[row] > 0) {
+                continue;
+            }
+            let expanded = self.expand_cube(cube, offset);
+            self.add(expanded);
+        }
+    }
+
+    // Drops the cubes whose rows are all covered by other cubes, smallest first
+    fn irredundant(&mut self) {
+        self.cover
+            .sort_by_key(|cube| std::cmp::Reverse(cube.size()));
+        let mut i = self.cover.len();
+        while i > 0 {
+            i -= 1;
+            if self
+                .required_rows(self.cover[i])
+                .all(|row| self.counts[row] > 1)
+            {
+                self.remove(i);
+            }
+        }
+    }
+
+    // Shrinks every cube to the smallest one containing the rows no other
+    // cube covers, so that the next expansion can grow it another way
+    fn reduce(&mut self) {
+        let mut i = 0;
+        while i < self.cover.len() {
+            let cube = self.cover[i];
+            let (mut all, mut any, mut unique) = (!0, 0, false);
+            for row in self
+                .required_rows(cube)
+                .filter(|row| self.counts[*row] == 1)
+            {
+                all &= row as u32;
+                any |= row as u32;
+                unique = true;
+            }
+            self.remove(i);
+            if unique {
+                self.add(Implicant {
+                    value: all,
+                    mask: any & !all,
+                });
+                let last = self.cover.len() - 1;
+                self.cover.swap(i, last);
+                i += 1;
+            }
+        }
+    }
+
+    fn required_rows(&self, cube: Implicant) -> impl Iterator<Item = usize> + '_ {
+        cube.rows().filter(|row| (self.required)(*row))
+    }
+
+    fn cost(&self) -> (usize, usize) {
+        let literals = self.cover.iter().map(|c| c.literal_count(self.len)).sum();
+        (self.cover.len(), literals)
+    }
+}
+
+/// Minimises a cover of the function over `len` variables: the result covers
+/// every `required` row covered by `cover` and no row for which `valid` is
+/// false.
+///
+/// Each cube of `cover` must only contain valid rows. A count is kept for
+/// each of the 2^len rows, so `len` is bounded as for a truth table.
+pub fn minimise_cover(
+    len: usize,
+    cover: Vec<Implicant>,
+    required: &dyn Fn(usize) -> bool,
+    valid: &dyn Fn(usize) -> bool,
+) -> Vec<Implicant> {
+    let mut espresso = Espresso {
+        len,
+        required,
+        valid,
+        cover: Vec::new(),
+        counts: vec![0; 1 << len],
+    };
+    for cube in cover {
+        espresso.add(cube);
+    }
+    espresso.expand(0);
+    espresso.irredundant();
+    let mut best = (espresso.cost(), espresso.cover.clone());
+    for round in 1..MAX_ROUNDS {
+        espresso.reduce();
+        espresso.expand(round);
+        espresso.irredundant();
+        if espresso.cost() >= best.0 {
+            break;
+        }
+        best = (espresso.cost(), espresso.cover.clone());
+    }
+    let mut cover = best.1;
+    cover.sort();
+    cover
+}
+
+/// A small cover of the rows of `table` whose result is `value`, which may
+/// also cover any of its don't cares.
+///
+/// Every row of the table is visited to build the first cover, so the time
+/// taken grows with the 2^n rows of the table even when the result is small.
+pub fn minimise(table: &TruthTable, value: bool) -> Vec<Implicant> {
+    let len = table.variables.len();
+    let required = |row: usize| table.output(row) == Output::from(value);
+    let valid = |row: usize| table.output(row) != Output::from(!value);
+    let mut espresso = Espresso {
+        len,
+        required: &required,
+        valid: &valid,
+        cover: Vec::new(),
+        counts: vec![0; table.row_count()],
+    };
+    // Expanding each row not covered yet gives a first cover without
+    // ever listing all the minterms
+    for row in 0..table.row_count() {
+        if required(row) && espresso.counts[row] == 0 {
+            let cube = espresso.expand_cube(Implicant::minterm(row), 0);
+            espresso.add(cube);
+        }
+    }
+    minimise_cover(len, espresso.cover, &required, &valid)
+}
@@ -0,0 +1,170 @@
+use crate::formula::{Formula, Operator};
+use std::collections::HashMap;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+// Bits of a word where the bit `p < 6` of the row index is set
+const PATTERNS: [u64; 6] = [
+    0xAAAA_AAAA_AAAA_AAAA,
+    0xCCCC_CCCC_CCCC_CCCC,
+    0xF0F0_F0F0_F0F0_F0F0,
+    0xFF00_FF00_FF00_FF00,
+    0xFFFF_0000_FFFF_0000,
+    0xFFFF_FFFF_0000_0000,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Load(usize),
+    Const(bool),
+    Not,
+    Binary(Operator),
+    Cardinality {
+        arity: usize,
+        min: usize,
+        max: usize,
+    },
+}
+
+/// A formula compiled once into a stack program over 64-bit words.
+///
+/// Bit `i` of every word belongs to a separate assignment, so a single run
+/// evaluates 64 rows of the truth table at once.
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    variables: Vec<String>,
+    program: Vec<Instruction>,
+    depth: usize,
+}
+
+impl Evaluator {
+    pub fn new(formula: &Formula) -> Self {
+        let variables = formula.variables();
+        let indices = variables
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.as_str(), i))
+            .collect::<HashMap<&str, usize>>();
+        // The children come before their parent in the post-order
+        let mut program = Vec::new();
+        formula.fold(|node, _: Vec<()>| {
+            program.push(match node {
+                Formula::Var(name) => Instruction::Load(indices[name.as_str()]),
+                Formula::Const(b) => Instruction::Const(*b),
+                Formula::Not(_) => Instruction::Not,
+                _ => match (node.as_binary(), node.cardinality_bounds()) {
+                    (Some((op, _, _)), _) => Instruction::Binary(op),
+                    (_, Some((min, max))) => Instruction::Cardinality {
+                        arity: node.children().len(),
+                        min,
+                        max,
+                    },
+                    _ => unreachable!(),
+                },
+            })
+        });
+        // The largest number of words on the stack while running the program
+        let (mut height, mut depth) = (0, 0);
+        for instruction in &program {
+            height -= match *instruction {
+                Instruction::Load(_) | Instruction::Const(_) => 0,
+                Instruction::Not => 1,
+                Instruction::Binary(_) => 2,
+                Instruction::Cardinality { arity, .. } => arity,
+            };
+            height += 1;
+            depth = depth.max(height);
+        }
+        Evaluator {
+            variables,
+            program,
+            depth,
+        }
+    }
+
+    /// The variables of the formula, in the order their inputs are expected.
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    /// Evaluates the formula with `values[i]` assigned to `variables()[i]`.
+    pub fn evaluate(&self, values: &[bool]) -> bool {
+        let inputs = values
+            .iter()
+            .map(|b| if *b { !0 } else { 0 })
+            .collect::<Vec<u64>>();
+        self.evaluate_word(&inputs) & 1 == 1
+    }
+
+    /// Evaluates the formula on 64 assignments, bit `i` of `inputs[v]` being
+    /// the value of `variables()[v]` in assignment `i`.
+    pub fn evaluate_word(&self, inputs: &[u64]) -> u64 {
+        assert_eq!(inputs.len(), self.variables.len());
+        self.run(inputs, &mut Vec::with_capacity(self.depth))
+    }
+
+    fn run(&self, inputs: &[u64], stack: &mut Vec<u64>) -> u64 {
+        stack.clear();
+        for instruction in &self.program {
+            let word = match *instruction {
+                Instruction::Load(v) => inputs[v],
+                Instruction::Const(b) => {
+                    if b {
+                        !0
+                    } else {
+                        0
+                    }
+                }
+                Instruction::Not => !stack.pop().unwrap(),
+                Instruction::Binary(op) => {
+                    let rhs = stack.pop().unwrap();
+                    let lhs = stack.pop().unwrap();
+                    op.apply_word(lhs, rhs)
+                }
+                Instruction::Cardinality { arity, min, max } => {
+                    let operands = stack.split_off(stack.len() - arity);
+                    // at_least[j] has the bits where at least j operands are true
+                    let mut at_least = vec![0; min.max(max + 1) + 1];
+                    at_least[0] = !0;
+                    for x in operands {
+                        for j in (1..at_least.len()).rev() {
+                            at_least[j] |= at_least[j - 1] & x;
+                        }
+                    }
+                    at_least[min] & !at_least[max + 1]
+                }
+            };
+            stack.push(word);
+        }
+        stack.pop().unwrap()
+    }
+
+    /// The results of the truth table rows `64 * w..64 * (w + 1)` for each
+    /// word `w`, with the first variable as the most significant bit of the row.
+    ///
+    /// When the table has less than 64 rows, the bits past its end are zero.
+    pub fn words(&self) -> impl Iterator<Item = u64> + '_ {
+        let len = self.variables.len();
+        let rows = 1usize << len;
+        let mut inputs = vec![0; len];
+        let mut stack = Vec::with_capacity(self.depth);
+        (0..rows.div_ceil(WORD_BITS)).map(move |w| {
+            for (b, input) in inputs.iter_mut().enumerate() {
+                let p = len - 1 - b;
+                *input = if p < PATTERNS.len() {
+                    PATTERNS[p]
+                } else if (w >> (p - PATTERNS.len())) & 1 == 1 {
+                    !0
+                } else {
+                    0
+                };
+            }
+            let word = self.run(&inputs, &mut stack);
+            if rows < WORD_BITS {
+                word & ((1 << rows) - 1)
+            } else {
+                word
+            }
+        })
+    }
+}
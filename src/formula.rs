@@ -0,0 +1,815 @@
+use crate::assignment::Assignment;
+use crate::error::{ParseError, ParseErrorKind, UnassignedVariables};
+use anyhow::{anyhow, Result};
+use std::cmp::Ordering;
+use std::mem;
+use std::ops::Not;
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum Formula {
+    Var(String),
+    Const(bool),
+    Not(Box<Formula>),
+    And(Box<Formula>, Box<Formula>),
+    Or(Box<Formula>, Box<Formula>),
+    Xor(Box<Formula>, Box<Formula>),
+    Imply(Box<Formula>, Box<Formula>),
+    Equiv(Box<Formula>, Box<Formula>),
+    Nand(Box<Formula>, Box<Formula>),
+    Nor(Box<Formula>, Box<Formula>),
+    Converse(Box<Formula>, Box<Formula>),
+    AtMostOne(Vec<Formula>),
+    ExactlyOne(Vec<Formula>),
+    Majority(Vec<Formula>),
+    AtLeast(usize, Vec<Formula>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operator {
+    And,
+    Or,
+    Xor,
+    Imply,
+    Equiv,
+    Nand,
+    Nor,
+    Converse,
+}
+
+impl Operator {
+    pub fn apply(self, lhs: Formula, rhs: Formula) -> Formula {
+        let (lhs, rhs) = (Box::new(lhs), Box::new(rhs));
+        match self {
+            Operator::And => Formula::And(lhs, rhs),
+            Operator::Or => Formula::Or(lhs, rhs),
+            Operator::Xor => Formula::Xor(lhs, rhs),
+            Operator::Imply => Formula::Imply(lhs, rhs),
+            Operator::Equiv => Formula::Equiv(lhs, rhs),
+            Operator::Nand => Formula::Nand(lhs, rhs),
+            Operator::Nor => Formula::Nor(lhs, rhs),
+            Operator::Converse => Formula::Converse(lhs, rhs),
+        }
+    }
+
+    pub fn rpn_symbol(self) -> char {
+        match self {
+            Operator::And => '&',
+            Operator::Or => '|',
+            Operator::Xor => '^',
+            Operator::Imply => '>',
+            Operator::Equiv => '=',
+            Operator::Nand => '↑',
+            Operator::Nor => '↓',
+            Operator::Converse => '<',
+        }
+    }
+
+    pub fn from_rpn_symbol(c: char) -> Option<Operator> {
+        Some(match c {
+            '&' => Operator::And,
+            '|' => Operator::Or,
+            '^' => Operator::Xor,
+            '>' => Operator::Imply,
+            '=' => Operator::Equiv,
+            '↑' => Operator::Nand,
+            '↓' => Operator::Nor,
+            '<' => Operator::Converse,
+            _ => return None,
+        })
+    }
+
+    /// Applies the operator bitwise, evaluating 64 rows at once.
+    pub fn apply_word(self, lhs: u64, rhs: u64) -> u64 {
+        match self {
+            Operator::And => lhs & rhs,
+            Operator::Or => lhs | rhs,
+            Operator::Xor => lhs ^ rhs,
+            Operator::Imply => !lhs | rhs,
+            Operator::Equiv => !(lhs ^ rhs),
+            Operator::Nand => !(lhs & rhs),
+            Operator::Nor => !(lhs | rhs),
+            Operator::Converse => lhs | !rhs,
+        }
+    }
+}
+
+impl Formula {
+    pub fn and(lhs: Formula, rhs: Formula) -> Formula {
+        Operator::And.apply(lhs, rhs)
+    }
+
+    pub fn or(lhs: Formula, rhs: Formula) -> Formula {
+        Operator::Or.apply(lhs, rhs)
+    }
+
+    pub fn xor(lhs: Formula, rhs: Formula) -> Formula {
+        Operator::Xor.apply(lhs, rhs)
+    }
+
+    pub fn imply(lhs: Formula, rhs: Formula) -> Formula {
+        Operator::Imply.apply(lhs, rhs)
+    }
+
+    pub fn equiv(lhs: Formula, rhs: Formula) -> Formula {
+        Operator::Equiv.apply(lhs, rhs)
+    }
+
+    pub fn nand(lhs: Formula, rhs: Formula) -> Formula {
+        Operator::Nand.apply(lhs, rhs)
+    }
+
+    pub fn nor(lhs: Formula, rhs: Formula) -> Formula {
+        Operator::Nor.apply(lhs, rhs)
+    }
+
+    pub fn converse(lhs: Formula, rhs: Formula) -> Formula {
+        Operator::Converse.apply(lhs, rhs)
+    }
+
+    pub fn var(name: &str) -> Formula {
+        Formula::Var(String::from(name))
+    }
+
+    // Right-nested, so that the operators end up at the end of the RPN form
+    pub fn conjunction<I: IntoIterator<Item = Formula>>(formulas: I) -> Formula {
+        let formulas = formulas.into_iter().collect::<Vec<_>>();
+        formulas
+            .into_iter()
+            .rev()
+            .reduce(|acc, f| Formula::and(f, acc))
+            .unwrap_or(Formula::Const(true))
+    }
+
+    pub fn disjunction<I: IntoIterator<Item = Formula>>(formulas: I) -> Formula {
+        let formulas = formulas.into_iter().collect::<Vec<_>>();
+        formulas
+            .into_iter()
+            .rev()
+            .reduce(|acc, f| Formula::or(f, acc))
+            .unwrap_or(Formula::Const(false))
+    }
+
+    // `amo`, `exo` and `maj` are fixed, `atleast<k>` is the threshold
+    // connective that is true when at least k operands are true.
+    pub fn nary(name: &str, operands: Vec<Formula>) -> Option<Formula> {
+        match name {
+            "amo" => Some(Formula::AtMostOne(operands)),
+            "exo" => Some(Formula::ExactlyOne(operands)),
+            "maj" => Some(Formula::Majority(operands)),
+            _ => {
+                let k = name.strip_prefix("atleast")?;
+                if k.is_empty() || !k.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                Some(Formula::AtLeast(k.parse().ok()?, operands))
+            }
+        }
+    }
+
+    pub fn nary_name(&self) -> Option<String> {
+        Some(match self {
+            Formula::AtMostOne(_) => String::from("amo"),
+            Formula::ExactlyOne(_) => String::from("exo"),
+            Formula::Majority(_) => String::from("maj"),
+            Formula::AtLeast(k, _) => format!("atleast{}", k),
+            _ => return None,
+        })
+    }
+
+    pub fn as_binary(&self) -> Option<(Operator, &Formula, &Formula)> {
+        let (op, lhs, rhs) = match self {
+            Formula::And(lhs, rhs) => (Operator::And, lhs, rhs),
+            Formula::Or(lhs, rhs) => (Operator::Or, lhs, rhs),
+            Formula::Xor(lhs, rhs) => (Operator::Xor, lhs, rhs),
+            Formula::Imply(lhs, rhs) => (Operator::Imply, lhs, rhs),
+            Formula::Equiv(lhs, rhs) => (Operator::Equiv, lhs, rhs),
+            Formula::Nand(lhs, rhs) => (Operator::Nand, lhs, rhs),
+            Formula::Nor(lhs, rhs) => (Operator::Nor, lhs, rhs),
+            Formula::Converse(lhs, rhs) => (Operator::Converse, lhs, rhs),
+            _ => return None,
+        };
+        Some((op, lhs, rhs))
+    }
+
+    pub fn children(&self) -> Vec<&Formula> {
+        match self {
+            Formula::Var(_) | Formula::Const(_) => Vec::new(),
+            Formula::Not(child) => vec![child],
+            Formula::AtMostOne(operands)
+            | Formula::ExactlyOne(operands)
+            | Formula::Majority(operands)
+            | Formula::AtLeast(_, operands) => operands.iter().collect(),
+            _ => {
+                let (_, lhs, rhs) = self.as_binary().unwrap();
+                vec![lhs, rhs]
+            }
+        }
+    }
+
+    pub fn with_children(&self, mut children: Vec<Formula>) -> Formula {
+        match self {
+            Formula::Var(name) => Formula::Var(name.clone()),
+            Formula::Const(b) => Formula::Const(*b),
+            Formula::Not(_) => !children.remove(0),
+            Formula::AtMostOne(_) => Formula::AtMostOne(children),
+            Formula::ExactlyOne(_) => Formula::ExactlyOne(children),
+            Formula::Majority(_) => Formula::Majority(children),
+            Formula::AtLeast(k, _) => Formula::AtLeast(*k, children),
+            _ => {
+                let (op, _, _) = self.as_binary().unwrap();
+                let rhs = children.pop().unwrap();
+                op.apply(children.pop().unwrap(), rhs)
+            }
+        }
+    }
+
+    /// Computes `f` on every node from the node and the results of its
+    /// children, children first. The nodes are visited with an explicit
+    /// stack, so that formulas nested deeper than the call stack allows can
+    /// be traversed.
+    pub(crate) fn fold<'a, T, F>(&'a self, mut f: F) -> T
+    where
+        F: FnMut(&'a Formula, Vec<T>) -> T,
+    {
+        let mut stack = vec![(self, false)];
+        let mut results = Vec::new();
+        while let Some((node, visited)) = stack.pop() {
+            let children = node.children();
+            if visited {
+                let values = results.split_off(results.len() - children.len());
+                results.push(f(node, values));
+            } else {
+                stack.push((node, true));
+                stack.extend(children.into_iter().rev().map(|child| (child, false)));
+            }
+        }
+        results.pop().unwrap()
+    }
+
+    // Moves the children out of the node, leaving constants in their place
+    fn take_children(&mut self, children: &mut Vec<Formula>) {
+        let mut take = |child: &mut Box<Formula>| {
+            children.push(mem::replace(&mut **child, Formula::Const(false)))
+        };
+        match self {
+            Formula::Var(_) | Formula::Const(_) => (),
+            Formula::Not(child) => take(child),
+            Formula::And(lhs, rhs)
+            | Formula::Or(lhs, rhs)
+            | Formula::Xor(lhs, rhs)
+            | Formula::Imply(lhs, rhs)
+            | Formula::Equiv(lhs, rhs)
+            | Formula::Nand(lhs, rhs)
+            | Formula::Nor(lhs, rhs)
+            | Formula::Converse(lhs, rhs) => {
+                take(lhs);
+                take(rhs);
+            }
+            Formula::AtMostOne(operands)
+            | Formula::ExactlyOne(operands)
+            | Formula::Majority(operands)
+            | Formula::AtLeast(_, operands) => children.append(operands),
+        }
+    }
+
+    fn into_children(mut self) -> Vec<Formula> {
+        let mut children = Vec::new();
+        self.take_children(&mut children);
+        children
+    }
+
+    // The value of this connective given the values of its children
+    pub(crate) fn truth_function(&self, values: &[bool]) -> bool {
+        let count = values.iter().filter(|b| **b).count();
+        match self {
+            Formula::Var(name) => panic!("Variable '{}' has no truth function", name),
+            Formula::Const(b) => *b,
+            Formula::Not(_) => !values[0],
+            Formula::And(_, _) => values[0] & values[1],
+            Formula::Or(_, _) => values[0] | values[1],
+            Formula::Xor(_, _) => values[0] ^ values[1],
+            Formula::Imply(_, _) => !values[0] | values[1],
+            Formula::Equiv(_, _) => values[0] == values[1],
+            Formula::Nand(_, _) => !(values[0] & values[1]),
+            Formula::Nor(_, _) => !(values[0] | values[1]),
+            Formula::Converse(_, _) => values[0] | !values[1],
+            Formula::AtMostOne(_) => count <= 1,
+            Formula::ExactlyOne(_) => count == 1,
+            Formula::Majority(_) => 2 * count > values.len(),
+            Formula::AtLeast(k, _) => count >= *k,
+        }
+    }
+
+    pub fn parse_rpn(formula: &str) -> Result<Formula, ParseError> {
+        let error =
+            |kind, offset, depth, expected| ParseError::new(kind, formula, offset, depth, expected);
+        let mut stack = Vec::new();
+        for (i, token) in rpn_tokens(formula) {
+            let mut chars = token.chars();
+            let operator = match (chars.next(), chars.next()) {
+                (Some(c), None) => Operator::from_rpn_symbol(c),
+                _ => None,
+            };
+            match token {
+                "0" => stack.push(Formula::Const(false)),
+                "1" => stack.push(Formula::Const(true)),
+                "!" => {
+                    let child = stack.pop().ok_or_else(|| {
+                        error(
+                            ParseErrorKind::MissingOperand,
+                            i,
+                            0,
+                            "an operand before '!'",
+                        )
+                    })?;
+                    stack.push(!child);
+                }
+                _ if operator.is_some() => {
+                    if stack.len() < 2 {
+                        return Err(error(
+                            ParseErrorKind::MissingOperand,
+                            i,
+                            stack.len(),
+                            "two operands before a binary operator",
+                        ));
+                    }
+                    let (rhs, lhs) = (stack.pop().unwrap(), stack.pop().unwrap());
+                    stack.push(operator.unwrap().apply(lhs, rhs));
+                }
+                _ if identifier_length(token) == token.len() => stack.push(Formula::var(token)),
+                _ if token.contains('/') => {
+                    let (name, arity) = token.split_once('/').unwrap();
+                    let arity = arity
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|_| Formula::nary(name, Vec::new()).is_some());
+                    let arity = arity.ok_or_else(|| {
+                        error(
+                            ParseErrorKind::UnexpectedToken,
+                            i,
+                            stack.len(),
+                            "an n-ary operator such as amo/3, exo/3, maj/3 or atleast2/3",
+                        )
+                    })?;
+                    if stack.len() < arity {
+                        return Err(error(
+                            ParseErrorKind::MissingOperand,
+                            i,
+                            stack.len(),
+                            "as many operands as the arity of the operator",
+                        ));
+                    }
+                    let operands = stack.split_off(stack.len() - arity);
+                    stack.push(Formula::nary(name, operands).unwrap());
+                }
+                _ => {
+                    return Err(error(
+                        ParseErrorKind::UnexpectedCharacter,
+                        i + identifier_length(token),
+                        stack.len(),
+                        "a variable, a constant or an operator",
+                    ))
+                }
+            }
+        }
+        match stack.len() {
+            0 => Err(error(
+                ParseErrorKind::UnexpectedEnd,
+                formula.len(),
+                0,
+                "a variable or a constant",
+            )),
+            1 => Ok(stack.pop().unwrap()),
+            depth => Err(error(
+                ParseErrorKind::TrailingOperands,
+                formula.len(),
+                depth,
+                "an operator",
+            )),
+        }
+    }
+
+    pub fn to_rpn(&self) -> String {
+        let mut tokens = Vec::new();
+        self.fold(|node, _: Vec<()>| {
+            tokens.push(match node {
+                Formula::Var(name) => name.clone(),
+                Formula::Const(b) => String::from(if *b { "1" } else { "0" }),
+                Formula::Not(_) => String::from("!"),
+                _ => match (node.as_binary(), node.nary_name()) {
+                    (Some((op, _, _)), _) => op.rpn_symbol().to_string(),
+                    (_, Some(name)) => format!("{}/{}", name, node.children().len()),
+                    _ => unreachable!(),
+                },
+            })
+        });
+        join_rpn(&tokens)
+    }
+
+    pub fn variables(&self) -> Vec<String> {
+        let mut variables = Vec::new();
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match node {
+                Formula::Var(name) => variables.push(name.as_str()),
+                _ => stack.extend(node.children()),
+            }
+        }
+        variables.sort_by(|a, b| compare_names(a, b));
+        variables.dedup();
+        variables.into_iter().map(String::from).collect()
+    }
+
+    pub fn replace_variables<F>(&self, replace: &F) -> Formula
+    where
+        F: Fn(&str) -> Option<Formula>,
+    {
+        self.fold(|node, children| match node {
+            Formula::Var(name) => replace(name).unwrap_or_else(|| node.clone()),
+            _ => node.with_children(children),
+        })
+    }
+
+    pub fn evaluate(&self) -> Result<bool> {
+        self.fold(|node, values: Vec<Result<bool>>| match node {
+            Formula::Var(name) => Err(anyhow!("Unassigned variable '{}'", name)),
+            _ => Ok(node.truth_function(&values.into_iter().collect::<Result<Vec<bool>>>()?)),
+        })
+    }
+
+    /// Substitutes the assigned variables and simplifies the residual formula.
+    pub fn assign(&self, assignment: &Assignment) -> Formula {
+        self.replace_variables(&|name| assignment.get(name).map(Formula::Const))
+            .fold_constants()
+    }
+
+    /// Evaluates the formula under `assignment`.
+    ///
+    /// When the value still depends on unassigned variables the error holds
+    /// the residual formula.
+    pub fn eval(&self, assignment: &Assignment) -> Result<bool, UnassignedVariables> {
+        match self.assign(assignment) {
+            Formula::Const(b) => Ok(b),
+            residual => Err(UnassignedVariables { residual }),
+        }
+    }
+
+    pub fn fold_constants(&self) -> Formula {
+        self.fold(|node, children| node.fold_node(children))
+    }
+
+    // Folds the constants of a node whose children are already folded
+    fn fold_node(&self, children: Vec<Formula>) -> Formula {
+        let negate = |f: Formula| match f {
+            Formula::Const(b) => Formula::Const(!b),
+            Formula::Not(_) => f.into_children().pop().unwrap(),
+            f => !f,
+        };
+        let mut children = children.into_iter();
+        let mut child = || children.next().unwrap();
+        match self {
+            Formula::Var(_) | Formula::Const(_) => self.clone(),
+            Formula::Not(_) => negate(child()),
+            Formula::And(..) => match (child(), child()) {
+                (Formula::Const(false), _) | (_, Formula::Const(false)) => Formula::Const(false),
+                (Formula::Const(true), f) | (f, Formula::Const(true)) => f,
+                (lhs, rhs) => Formula::and(lhs, rhs),
+            },
+            Formula::Or(..) => match (child(), child()) {
+                (Formula::Const(true), _) | (_, Formula::Const(true)) => Formula::Const(true),
+                (Formula::Const(false), f) | (f, Formula::Const(false)) => f,
+                (lhs, rhs) => Formula::or(lhs, rhs),
+            },
+            Formula::Xor(..) => match (child(), child()) {
+                (Formula::Const(b), f) | (f, Formula::Const(b)) => {
+                    if b {
+                        negate(f)
+                    } else {
+                        f
+                    }
+                }
+                (lhs, rhs) => Formula::xor(lhs, rhs),
+            },
+            Formula::Imply(..) => match (child(), child()) {
+                (Formula::Const(false), _) | (_, Formula::Const(true)) => Formula::Const(true),
+                (Formula::Const(true), f) => f,
+                (f, Formula::Const(false)) => negate(f),
+                (lhs, rhs) => Formula::imply(lhs, rhs),
+            },
+            Formula::Equiv(..) => match (child(), child()) {
+                (Formula::Const(b), f) | (f, Formula::Const(b)) => {
+                    if b {
+                        f
+                    } else {
+                        negate(f)
+                    }
+                }
+                (lhs, rhs) => Formula::equiv(lhs, rhs),
+            },
+            Formula::Nand(..) => match (child(), child()) {
+                (Formula::Const(false), _) | (_, Formula::Const(false)) => Formula::Const(true),
+                (Formula::Const(true), f) | (f, Formula::Const(true)) => negate(f),
+                (lhs, rhs) => Formula::nand(lhs, rhs),
+            },
+            Formula::Nor(..) => match (child(), child()) {
+                (Formula::Const(true), _) | (_, Formula::Const(true)) => Formula::Const(false),
+                (Formula::Const(false), f) | (f, Formula::Const(false)) => negate(f),
+                (lhs, rhs) => Formula::nor(lhs, rhs),
+            },
+            Formula::Converse(..) => match (child(), child()) {
+                (Formula::Const(true), _) | (_, Formula::Const(false)) => Formula::Const(true),
+                (Formula::Const(false), f) => negate(f),
+                (f, Formula::Const(true)) => f,
+                (lhs, rhs) => Formula::converse(lhs, rhs),
+            },
+            _ => {
+                let (min, max) = self.cardinality_bounds().unwrap();
+                let mut operands = Vec::new();
+                let mut count = 0;
+                for child in children {
+                    match child {
+                        Formula::Const(b) => count += b as usize,
+                        child => operands.push(child),
+                    }
+                }
+                // Kept as written unless an operand was folded away or the
+                // bounds do not depend on the operands
+                let n = operands.len();
+                let trivial = min > max.min(n) || (min == 0 && max >= n);
+                if count == 0 && n == self.children().len() && !trivial {
+                    return self.with_children(operands);
+                }
+                match max.checked_sub(count) {
+                    Some(max) => cardinality(min.saturating_sub(count), max, operands),
+                    None => Formula::Const(false),
+                }
+            }
+        }
+    }
+
+    // The inclusive range of true operands accepted by an n-ary connective
+    pub(crate) fn cardinality_bounds(&self) -> Option<(usize, usize)> {
+        let n = self.children().len();
+        match self {
+            Formula::AtMostOne(_) => Some((0, 1)),
+            Formula::ExactlyOne(_) => Some((1, 1)),
+            Formula::Majority(_) => Some((n / 2 + 1, n)),
+            Formula::AtLeast(k, _) => Some((*k, n)),
+            _ => None,
+        }
+    }
+
+    // Rewrites the n-ary connectives into the binary ones
+    pub fn expand_nary(&self) -> Formula {
+        self.fold(|node, children| {
+            let expanded = node.with_children(children);
+            match node.nary_name() {
+                Some(_) => expanded.expand_node(),
+                None => expanded,
+            }
+        })
+    }
+
+    fn expand_node(&self) -> Formula {
+        let operands = self.children().into_iter().cloned().collect::<Vec<_>>();
+        let at_most_one = |operands: &[Formula]| {
+            Formula::conjunction(operands.iter().enumerate().flat_map(|(i, lhs)| {
+                operands[i + 1..]
+                    .iter()
+                    .map(|rhs| Formula::or(!lhs.clone(), !rhs.clone()))
+            }))
+        };
+        match self {
+            Formula::AtMostOne(_) => at_most_one(&operands),
+            Formula::ExactlyOne(_) => Formula::and(
+                Formula::disjunction(operands.clone()),
+                at_most_one(&operands),
+            ),
+            Formula::Majority(_) => at_least(operands.len() / 2 + 1, &operands),
+            Formula::AtLeast(k, _) => at_least(*k, &operands),
+            _ => self.clone(),
+        }
+        .fold_constants()
+    }
+
+    pub fn to_nnf(&self) -> Formula {
+        // The negation normal form of every node and of its negation,
+        // children first
+        let nnf = |node: &Formula, mut children: Vec<(Formula, Formula)>| {
+            if node.as_binary().is_none() {
+                return match node {
+                    Formula::Var(_) => (node.clone(), !node.clone()),
+                    Formula::Const(b) => (Formula::Const(*b), Formula::Const(!b)),
+                    Formula::Not(_) => {
+                        let (nnf, negated) = children.pop().unwrap();
+                        (negated, nnf)
+                    }
+                    _ => unreachable!("the n-ary connectives are expanded first"),
+                };
+            }
+            let ((l, nl), (r, nr)) = (children.remove(0), children.remove(0));
+            match node {
+                Formula::And(..) => (Formula::and(l, r), Formula::or(nl, nr)),
+                Formula::Or(..) => (Formula::or(l, r), Formula::and(nl, nr)),
+                Formula::Xor(..) => (
+                    Formula::and(
+                        Formula::or(l.clone(), r.clone()),
+                        Formula::or(nl.clone(), nr.clone()),
+                    ),
+                    Formula::or(Formula::and(nl, nr), Formula::and(l, r)),
+                ),
+                Formula::Imply(..) => (Formula::or(nl, r), Formula::and(l, nr)),
+                Formula::Equiv(..) => (
+                    Formula::and(
+                        Formula::or(nl.clone(), r.clone()),
+                        Formula::or(nr.clone(), l.clone()),
+                    ),
+                    Formula::or(Formula::and(l, nr), Formula::and(r, nl)),
+                ),
+                Formula::Nand(..) => (Formula::or(nl, nr), Formula::and(l, r)),
+                Formula::Nor(..) => (Formula::and(nl, nr), Formula::or(l, r)),
+                Formula::Converse(..) => (Formula::or(l, nr), Formula::and(nl, r)),
+                _ => unreachable!(),
+            }
+        };
+        self.fold_constants().expand_nary().fold(nnf).0
+    }
+}
+
+impl Clone for Formula {
+    fn clone(&self) -> Formula {
+        self.fold(|node, children| node.with_children(children))
+    }
+}
+
+// The default drop recurses once per level of nesting, so the nodes are
+// moved onto a stack and dropped one at a time instead
+impl Drop for Formula {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        self.take_children(&mut stack);
+        while let Some(mut node) = stack.pop() {
+            node.take_children(&mut stack);
+        }
+    }
+}
+
+impl Not for Formula {
+    type Output = Formula;
+
+    fn not(self) -> Formula {
+        Formula::Not(Box::new(self))
+    }
+}
+
+// At least k of the operands, as a disjunction over the ways to split the k
+// true operands between the two halves of the operands. The thresholds of
+// each half are built once and shared by every term, which keeps the
+// expansion to n^O(log n) nodes instead of the C(n, k) of a split on the
+// first operand.
+fn at_least(k: usize, operands: &[Formula]) -> Formula {
+    if k == 0 {
+        Formula::Const(true)
+    } else if operands.len() < k {
+        Formula::Const(false)
+    } else if k == 1 {
+        Formula::disjunction(operands.iter().cloned())
+    } else if k == operands.len() {
+        Formula::conjunction(operands.iter().cloned())
+    } else {
+        let (lhs, rhs) = operands.split_at(operands.len() / 2);
+        split_at_least(k, &thresholds(lhs), &thresholds(rhs))
+    }
+}
+
+// The formulas for at least 0, 1, ..., n of the operands
+fn thresholds(operands: &[Formula]) -> Vec<Formula> {
+    match operands {
+        [operand] => vec![Formula::Const(true), operand.clone()],
+        _ => {
+            let (lhs, rhs) = operands.split_at(operands.len() / 2);
+            let (lhs, rhs) = (thresholds(lhs), thresholds(rhs));
+            (0..=operands.len())
+                .map(|k| split_at_least(k, &lhs, &rhs))
+                .collect()
+        }
+    }
+}
+
+// At least k operands from the thresholds of two halves, with `lhs[0]` and
+// `rhs[0]` being true
+fn split_at_least(k: usize, lhs: &[Formula], rhs: &[Formula]) -> Formula {
+    let terms =
+        (k.saturating_sub(rhs.len() - 1)..=k.min(lhs.len() - 1)).map(|i| match (i, k - i) {
+            (0, j) => rhs[j].clone(),
+            (i, 0) => lhs[i].clone(),
+            (i, j) => Formula::and(lhs[i].clone(), rhs[j].clone()),
+        });
+    Formula::disjunction(terms)
+}
+
+fn cardinality(min: usize, max: usize, operands: Vec<Formula>) -> Formula {
+    let n = operands.len();
+    match (min, max) {
+        _ if min > max.min(n) => Formula::Const(false),
+        (0, _) if max >= n => Formula::Const(true),
+        (0, 0) => !Formula::AtLeast(1, operands),
+        (0, 1) => Formula::AtMostOne(operands),
+        (1, 1) => Formula::ExactlyOne(operands),
+        _ => Formula::AtLeast(min, operands),
+    }
+}
+
+fn is_rpn_operator(c: char) -> bool {
+    c == '!' || Operator::from_rpn_symbol(c).is_some()
+}
+
+// The tokens without separators when they are all one character long, and
+// separated by spaces otherwise, which `rpn_tokens` splits back the same way.
+// A lone multi-character variable gets a trailing space, as it would be read
+// one character at a time without whitespace.
+pub(crate) fn join_rpn<S: AsRef<str>>(tokens: &[S]) -> String {
+    let tokens = tokens.iter().map(AsRef::as_ref).collect::<Vec<&str>>();
+    match tokens[..] {
+        [token] if token.chars().count() > 1 => format!("{} ", token),
+        _ if tokens.iter().all(|token| token.chars().count() == 1) => tokens.concat(),
+        _ => tokens.join(" "),
+    }
+}
+
+// Without whitespace every character is a token, which keeps the compact
+// single-letter notation (`AB&C|`) working. Once whitespace is present,
+// variables are whole identifiers separated by whitespace or operators.
+fn rpn_tokens(formula: &str) -> Vec<(usize, &str)> {
+    if !formula.contains(char::is_whitespace) {
+        return formula
+            .char_indices()
+            .map(|(i, c)| (i, &formula[i..i + c.len_utf8()]))
+            .collect();
+    }
+    let mut tokens = Vec::new();
+    let mut chars = formula.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if is_rpn_operator(c) {
+            tokens.push((i, &formula[i..i + c.len_utf8()]));
+        } else if !c.is_whitespace() {
+            let mut end = i + c.len_utf8();
+            while let Some((j, c)) =
+                chars.next_if(|(_, c)| !c.is_whitespace() && !is_rpn_operator(*c))
+            {
+                end = j + c.len_utf8();
+            }
+            tokens.push((i, &formula[i..end]));
+        }
+    }
+    tokens
+}
+
+pub(crate) fn identifier_length(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    if !bytes
+        .first()
+        .is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_')
+    {
+        return 0;
+    }
+    let mut len = 1 + bytes[1..]
+        .iter()
+        .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+        .count();
+    while bytes.get(len) == Some(&b'[') {
+        let digits = bytes[len + 1..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if digits == 0 || bytes.get(len + 1 + digits) != Some(&b']') {
+            break;
+        }
+        len += digits + 2;
+    }
+    len
+}
+
+pub fn compare_names(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    while let (Some(x), Some(y)) = (a.chars().next(), b.chars().next()) {
+        let ordering = if x.is_ascii_digit() && y.is_ascii_digit() {
+            let split = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+            let (i, j) = (split(a), split(b));
+            let (m, n) = (
+                a[..i].trim_start_matches('0'),
+                b[..j].trim_start_matches('0'),
+            );
+            let ordering = m.len().cmp(&n.len()).then(m.cmp(n));
+            (a, b) = (&a[i..], &b[j..]);
+            ordering
+        } else {
+            (a, b) = (&a[x.len_utf8()..], &b[y.len_utf8()..]);
+            x.cmp(&y)
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
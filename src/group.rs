@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Group(pub HashSet<u32>);
 
 impl Group {
@@ -13,7 +13,7 @@ impl Group {
         self.0.insert(i)
     }
 
-    pub fn union<'a>(self, other: &Group) -> Group {
+    pub fn union(self, other: &Group) -> Group {
         let mut u = self.0;
         u.extend(&other.0);
         Group(u)
@@ -22,6 +22,10 @@ impl Group {
     pub fn len(&self) -> usize {
         self.0.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
 
 impl PartialEq for Group {
@@ -0,0 +1,344 @@
+use crate::error::{ParseError, ParseErrorKind};
+use crate::formula::{identifier_length, Formula, Operator};
+use std::fmt;
+use std::iter::Peekable;
+
+impl Operator {
+    pub fn precedence(self) -> u8 {
+        match self {
+            Operator::Equiv => 1,
+            Operator::Imply => 2,
+            Operator::Converse => 3,
+            Operator::Or | Operator::Nor => 4,
+            Operator::Xor => 5,
+            Operator::And | Operator::Nand => 6,
+        }
+    }
+
+    pub fn is_right_associative(self) -> bool {
+        self == Operator::Imply
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Operator::And => "&",
+            Operator::Or => "|",
+            Operator::Xor => "^",
+            Operator::Imply => "->",
+            Operator::Equiv => "<->",
+            Operator::Nand => "↑",
+            Operator::Nor => "↓",
+            Operator::Converse => "<-",
+        }
+    }
+}
+
+const NOT_PRECEDENCE: u8 = 7;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Leaf(Formula),
+    Not,
+    Binary(Operator),
+    Function(String),
+    Open,
+    Close,
+    Comma,
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl Lexer<'_> {
+    fn eat(&mut self, expected: &str) -> bool {
+        let matched = self.input[self.position..].starts_with(expected);
+        if matched {
+            self.position += expected.len();
+        }
+        matched
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Result<(usize, Token), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.input[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+        let i = self.position;
+        let c = self.input[i..].chars().next()?;
+        let length = identifier_length(&self.input[i..]);
+        if length > 0 {
+            let name = &self.input[i..i + length];
+            self.position += length;
+            if self.input[self.position..].starts_with('(')
+                && Formula::nary(name, Vec::new()).is_some()
+            {
+                return Some(Ok((i, Token::Function(String::from(name)))));
+            }
+            return Some(Ok((i, Token::Leaf(Formula::var(name)))));
+        }
+        self.position += c.len_utf8();
+        let token = match c {
+            '0' => Token::Leaf(Formula::Const(false)),
+            '1' => Token::Leaf(Formula::Const(true)),
+            '!' => Token::Not,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            '-' if self.eat(">") => Token::Binary(Operator::Imply),
+            '<' if self.eat("->") => Token::Binary(Operator::Equiv),
+            '<' if self.eat("-") => Token::Binary(Operator::Converse),
+            // The reverse polish symbols, including `>`, `=` and `<` for `->`,
+            // `<->` and `<-`
+            _ if Operator::from_rpn_symbol(c).is_some() => {
+                Token::Binary(Operator::from_rpn_symbol(c).unwrap())
+            }
+            _ => {
+                return Some(Err(ParseError::new(
+                    ParseErrorKind::UnexpectedCharacter,
+                    self.input,
+                    i,
+                    0,
+                    "a variable, a constant, an operator or a parenthesis",
+                )))
+            }
+        };
+        Some(Ok((i, token)))
+    }
+}
+
+// The constructs still open while parsing, innermost last
+enum Frame {
+    Not,
+    Binary(Operator),
+    Group,
+    Function(String, Vec<Formula>),
+}
+
+struct InfixParser<'a> {
+    input: &'a str,
+    tokens: Peekable<Lexer<'a>>,
+    depth: usize,
+}
+
+impl InfixParser<'_> {
+    fn next(&mut self) -> Result<Option<(usize, Token)>, ParseError> {
+        let depth = self.depth;
+        self.tokens
+            .next()
+            .transpose()
+            .map_err(|error| ParseError { depth, ..error })
+    }
+
+    fn error(&self, offset: Option<usize>, expected: &'static str) -> ParseError {
+        let kind = match offset {
+            Some(_) => ParseErrorKind::UnexpectedToken,
+            None => ParseErrorKind::UnexpectedEnd,
+        };
+        let offset = offset.unwrap_or(self.input.len());
+        ParseError::new(kind, self.input, offset, self.depth, expected)
+    }
+
+    // The expected tokens after an operand, inside the innermost group
+    fn expected_after_operand(frames: &[Frame]) -> &'static str {
+        match frames
+            .iter()
+            .rev()
+            .find(|frame| !matches!(frame, Frame::Binary(_)))
+        {
+            Some(Frame::Group) => "an operator or ')'",
+            Some(Frame::Function(..)) => "an operator, ',' or ')'",
+            _ => "an operator",
+        }
+    }
+
+    // Operator precedence parsing with explicit stacks, so that the nesting
+    // depth of the formula is not bounded by the call stack
+    fn parse(&mut self) -> Result<Formula, ParseError> {
+        const OPERAND: &str = "a variable, a constant, '!' or '('";
+        let mut operands = Vec::new();
+        let mut frames = Vec::new();
+        loop {
+            // An operand, possibly under negations and opening parentheses
+            match self.next()? {
+                Some((_, Token::Leaf(leaf))) => operands.push(leaf),
+                Some((_, Token::Not)) => {
+                    frames.push(Frame::Not);
+                    continue;
+                }
+                Some((_, Token::Open)) => {
+                    self.depth += 1;
+                    frames.push(Frame::Group);
+                    continue;
+                }
+                Some((_, Token::Function(name))) => {
+                    self.next()?;
+                    self.depth += 1;
+                    if matches!(self.tokens.peek(), Some(Ok((_, Token::Close)))) {
+                        self.next()?;
+                        self.depth -= 1;
+                        operands.push(Formula::nary(&name, Vec::new()).unwrap());
+                    } else {
+                        frames.push(Frame::Function(name, Vec::new()));
+                        continue;
+                    }
+                }
+                token => return Err(self.error(token.map(|(i, _)| i), OPERAND)),
+            }
+            // Then the operators and closing parentheses that follow it
+            loop {
+                while let Some(Frame::Not) = frames.last() {
+                    frames.pop();
+                    let child = operands.pop().unwrap();
+                    operands.push(!child);
+                }
+                let next = self.next()?;
+                let precedence = match &next {
+                    Some((_, Token::Binary(op))) => op.precedence(),
+                    _ => 0,
+                };
+                while let Some(Frame::Binary(op)) = frames.last() {
+                    let op = *op;
+                    let shifts = match &next {
+                        Some((_, Token::Binary(next))) => {
+                            precedence > op.precedence()
+                                || (*next == op && op.is_right_associative())
+                        }
+                        _ => false,
+                    };
+                    if shifts {
+                        break;
+                    }
+                    frames.pop();
+                    let rhs = operands.pop().unwrap();
+                    let lhs = operands.pop().unwrap();
+                    operands.push(op.apply(lhs, rhs));
+                }
+                match (next, frames.last_mut()) {
+                    (Some((_, Token::Binary(op))), _) => {
+                        frames.push(Frame::Binary(op));
+                        break;
+                    }
+                    (Some((_, Token::Close)), Some(Frame::Group)) => {
+                        frames.pop();
+                        self.depth -= 1;
+                    }
+                    (Some((_, Token::Comma)), Some(Frame::Function(_, arguments))) => {
+                        arguments.push(operands.pop().unwrap());
+                        break;
+                    }
+                    (Some((_, Token::Close)), Some(Frame::Function(..))) => {
+                        let Some(Frame::Function(name, mut arguments)) = frames.pop() else {
+                            unreachable!()
+                        };
+                        arguments.push(operands.pop().unwrap());
+                        self.depth -= 1;
+                        operands.push(Formula::nary(&name, arguments).unwrap());
+                    }
+                    (None, None) => return Ok(operands.pop().unwrap()),
+                    (token, _) => {
+                        return Err(self
+                            .error(token.map(|(i, _)| i), Self::expected_after_operand(&frames)))
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Formula {
+    pub fn parse_infix(formula: &str) -> Result<Formula, ParseError> {
+        let mut parser = InfixParser {
+            input: formula,
+            tokens: Lexer {
+                input: formula,
+                position: 0,
+            }
+            .peekable(),
+            depth: 0,
+        };
+        parser.parse()
+    }
+
+    pub fn to_infix(&self) -> String {
+        self.to_string()
+    }
+
+    fn precedence(&self) -> u8 {
+        self.as_binary()
+            .map_or(NOT_PRECEDENCE, |(op, _, _)| op.precedence())
+    }
+}
+
+// What remains to be printed, the last piece first
+enum Piece<'a> {
+    Formula(&'a Formula),
+    Text(&'static str),
+    Operator(Operator),
+}
+
+fn push_child<'a>(pieces: &mut Vec<Piece<'a>>, child: &'a Formula, parenthesize: bool) {
+    if parenthesize {
+        pieces.extend([Piece::Text(")"), Piece::Formula(child), Piece::Text("(")]);
+    } else {
+        pieces.push(Piece::Formula(child));
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut pieces = vec![Piece::Formula(self)];
+        while let Some(piece) = pieces.pop() {
+            let node = match piece {
+                Piece::Formula(node) => node,
+                Piece::Text(text) => {
+                    f.write_str(text)?;
+                    continue;
+                }
+                Piece::Operator(op) => {
+                    write!(f, " {} ", op.symbol())?;
+                    continue;
+                }
+            };
+            match node {
+                Formula::Var(name) => write!(f, "{}", name)?,
+                Formula::Const(b) => write!(f, "{}", if *b { 1 } else { 0 })?,
+                Formula::Not(child) => {
+                    write!(f, "!")?;
+                    push_child(&mut pieces, child, child.precedence() < NOT_PRECEDENCE);
+                }
+                _ if node.nary_name().is_some() => {
+                    write!(f, "{}(", node.nary_name().unwrap())?;
+                    pieces.push(Piece::Text(")"));
+                    for (i, child) in node.children().into_iter().enumerate().rev() {
+                        pieces.push(Piece::Formula(child));
+                        if i > 0 {
+                            pieces.push(Piece::Text(", "));
+                        }
+                    }
+                }
+                _ => {
+                    let (op, lhs, rhs) = node.as_binary().unwrap();
+                    let precedence = op.precedence();
+                    push_child(
+                        &mut pieces,
+                        rhs,
+                        rhs.precedence() < precedence
+                            || (rhs.precedence() == precedence && !op.is_right_associative()),
+                    );
+                    pieces.push(Piece::Operator(op));
+                    push_child(
+                        &mut pieces,
+                        lhs,
+                        lhs.precedence() < precedence
+                            || (lhs.precedence() == precedence && op.is_right_associative()),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
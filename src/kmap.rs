@@ -1,19 +1,20 @@
 use crate::group::Group;
-use crate::truthtable::TruthTable;
+use crate::petrick::{self, Cost};
+use crate::quinemccluskey::Implicant;
+use crate::truthtable::{Output, TruthTable};
 use crate::*;
-use itertools::Itertools;
 use std::collections::HashSet;
 use std::fmt;
 
 pub struct KMap {
     x: usize,
     y: usize,
-    pub variables: Vec<char>,
-    map: Vec<Vec<(u32, bool)>>,
+    pub variables: Vec<String>,
+    map: Vec<Vec<(u32, Output)>>,
 }
 
 impl KMap {
-    pub fn from(table: TruthTable) -> Self {
+    pub fn from(table: &TruthTable) -> Self {
         let (y, x) = match table.variables.len() {
             1 => (2, 1),
             2 => (2, 2),
@@ -32,8 +33,8 @@ impl KMap {
                         .map(|i| gray_code(i as u32))
                         .map(|i| {
                             (
-                                (i + (j << x / 2)),
-                                table.results[(i + (j << x / 2)) as usize],
+                                (i + (j << (x / 2))),
+                                table.output((i + (j << (x / 2))) as usize),
                             )
                         })
                         .collect()
@@ -42,85 +43,86 @@ impl KMap {
         }
     }
 
-    fn get_transpose(&mut self) -> KMap {
-        let mut transpose: Vec<Vec<(u32, bool)>> = Vec::new();
-
-        for x in 0..self.x {
-            let mut v: Vec<(u32, bool)> = Vec::new();
-            for y in 0..self.y {
-                v.push(self.map[y][x]);
-            }
-            transpose.push(v);
-        }
-        KMap {
-            x: self.y,
-            y: self.x,
-            variables: self.variables.clone(),
-            map: transpose,
-        }
-    }
-
-    fn find_groups(&mut self, j: usize, i: usize) -> Group {
+    // The rectangles of 1, 2 or 4 by 1, 2 or 4 cells whose top left cell is
+    // at (j, i), wrapping around the edges, that contain no true cell
+    fn find_groups(&self, j: usize, i: usize) -> Vec<Group> {
         let (x, y) = (self.x, self.y);
-        let mut set = vec![self.map[j][i].0];
-        for i2 in (i + 1)..(i + x) {
-            if self.map[j][i2 % x].1 != false {
-                break;
-            }
-            set.push(self.map[j][i2 % x].0);
-        }
-        if set.len() == 3 {
-            set.truncate(2);
-        }
-        if set.len() == 2 {
-            for j2 in (j + 1)..(j + y) {
-                if self.map[j2 % y][i].1 != false || self.map[j2 % y][(i + 1) % x].1 != false {
-                    break;
+        let sizes = |n: usize| [1, 2, 4].into_iter().filter(move |s| *s <= n);
+        let mut groups = Vec::new();
+        for h in sizes(y) {
+            for w in sizes(x) {
+                let cells = (j..j + h)
+                    .flat_map(|j2| (i..i + w).map(move |i2| (j2 % y, i2 % x)))
+                    .map(|(j2, i2)| self.map[j2][i2]);
+                if cells.clone().all(|(_, output)| output != Output::True) {
+                    groups.push(cells.map(|(bit, _)| bit).collect());
                 }
-                set.push(self.map[j2 % y][i].0);
-                set.push(self.map[j2 % y][(i + 1) % x].0);
             }
         }
-        if set.len() == 6 {
-            set.truncate(4);
-        }
-        set.into_iter().collect()
+        groups
     }
 
-    fn get_groups(&mut self) -> HashSet<Group> {
-        let mut sets: HashSet<Group> = HashSet::new();
+    // The largest groups of cells without a true cell that contain a false cell
+    fn get_groups(&self) -> HashSet<Group> {
+        let mut groups = Vec::new();
         for j in 0..self.y {
             for i in 0..self.x {
-                if self.map[j][i].1 == false {
-                    sets.insert(vec![self.map[j][i].0].into_iter().collect());
-                    sets.insert(self.find_groups(j, i));
-                    sets.insert(self.get_transpose().find_groups(i, j));
-                }
+                groups.extend(self.find_groups(j, i));
             }
         }
-        sets
+        let falses = self
+            .map
+            .iter()
+            .flatten()
+            .filter(|(_, output)| *output == Output::False)
+            .map(|(bit, _)| *bit)
+            .collect::<HashSet<u32>>();
+        groups
+            .iter()
+            .filter(|group| !group.0.is_disjoint(&falses))
+            .filter(|group| {
+                !groups
+                    .iter()
+                    .any(|other| other.len() > group.len() && group.0.is_subset(&other.0))
+            })
+            .map(|group| Group(group.0.clone()))
+            .collect()
     }
 
-    pub fn get_minterms(&mut self) -> Option<Vec<Group>> {
-        let groups = self.get_groups();
-        let mut u = Group::new();
-        for group in groups.iter() {
-            u = u.union(&group);
-        }
-        let mut collection = groups.iter().collect::<Vec<_>>();
-        collection.sort_by(|a, b| b.len().cmp(&a.len()));
-        for count in 1..=collection.len() {
-            for combination in collection.iter().permutations(count) {
-                let mut union = Group::new();
-                for group in &combination {
-                    union = union.union(&group);
+    /// A minimum cover of the false cells by the groups of the map.
+    pub fn get_cover(&mut self) -> Option<Vec<Implicant>> {
+        let rows = self
+            .map
+            .iter()
+            .flatten()
+            .filter(|(_, output)| *output == Output::False)
+            .map(|(row, _)| *row as usize)
+            .collect::<Vec<usize>>();
+        let implicants = self
+            .get_groups()
+            .iter()
+            .map(|group| {
+                let all = group.0.iter().fold(!0, |all, row| all & row);
+                let any = group.0.iter().fold(0, |any, row| any | row);
+                Implicant {
+                    value: all,
+                    mask: any & !all,
                 }
-                if union.len() == u.len() {
-                    return Some(combination.iter().map(|c| Group(c.0.clone())).collect());
-                }
-            }
-        }
-        None
+            })
+            .collect::<Vec<Implicant>>();
+        petrick::minimum_covers(self.variables.len(), &implicants, &rows, Cost::Terms)
+            .into_iter()
+            .next()
+    }
+
+    pub fn get_minterms(&mut self) -> Option<Vec<Group>> {
+        let cover = self.get_cover()?;
+        Some(
+            cover
+                .iter()
+                .map(|implicant| implicant.rows().map(|row| row as u32).collect())
+                .collect(),
+        )
     }
 }
 
@@ -128,9 +130,9 @@ impl fmt::Display for KMap {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for y in &self.map {
             for (bit, b) in y {
-                write!(f, " {:2}:{:5} ", bit, b)?;
+                write!(f, " {:2}:{} ", bit, b)?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
@@ -1,14 +1,32 @@
+pub mod assignment;
+pub mod cdcl;
+pub mod cnf;
+pub mod dimacs;
+pub mod distribution;
+pub mod dpll;
+pub mod error;
+pub mod espresso;
+pub mod evaluator;
+pub mod formula;
 pub mod group;
+pub mod infix;
 pub mod kmap;
+pub mod models;
+pub mod mus;
 pub mod parser;
-pub mod permutationlist;
+pub mod petrick;
+pub mod quinemccluskey;
+// Only for the tests and benchmarks of the binaries
+#[doc(hidden)]
+pub mod random;
 pub mod truthtable;
+pub mod tseitin;
 
 pub fn adder(a: u32, b: u32) -> u32 {
     let (mut a, mut b) = (a, b);
     while b != 0 {
         let carry = (a & b) << 1;
-        a = a ^ b;
+        a ^= b;
         b = carry;
     }
     a
@@ -20,8 +38,8 @@ pub fn multiplier(a: u32, b: u32) -> u32 {
         if b & 1 != 0 {
             res = adder(res, a);
         }
-        a = a << 1;
-        b = b >> 1;
+        a <<= 1;
+        b >>= 1;
     }
     res
 }
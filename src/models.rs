@@ -0,0 +1,232 @@
+use crate::assignment::Assignment;
+use crate::cdcl::Solver;
+use crate::cnf::{Cnf, Lit};
+use crate::formula::Formula;
+use crate::tseitin::Encoding;
+use std::collections::HashMap;
+
+/// The models of a formula, found one at a time by the CDCL solver, which is
+/// then given a clause excluding the last model.
+///
+/// Every model assigns all the variables of the formula, including those
+/// that vanish with the constants, and the models come in no particular
+/// order.
+pub struct Models {
+    solver: Solver,
+    variables: Vec<String>,
+    done: bool,
+}
+
+// The CNF of the formula with the variables of the formula first, in order,
+// followed by the auxiliary variables of the encoding
+fn encode(formula: &Formula, encoding: Encoding) -> Cnf {
+    let mut cnf = formula.tseitin(encoding).cnf;
+    let variables = formula.variables();
+    let mut indices = vec![0; cnf.variables.len()];
+    let mut names = variables.clone();
+    for (i, name) in cnf.variables.iter().enumerate() {
+        indices[i] = match variables.iter().position(|v| v == name) {
+            Some(index) => index,
+            None => {
+                names.push(name.clone());
+                names.len() - 1
+            }
+        };
+    }
+    for clause in &mut cnf.clauses {
+        *clause = clause
+            .iter()
+            .map(|lit| Lit::new(indices[lit.var()], lit.is_positive()))
+            .collect();
+    }
+    cnf.variables = names;
+    cnf
+}
+
+impl Models {
+    pub fn new(formula: &Formula) -> Self {
+        let variables = formula.variables();
+        Models {
+            solver: Solver::new(&encode(formula, Encoding::Polarity)),
+            variables,
+            done: false,
+        }
+    }
+}
+
+impl Iterator for Models {
+    type Item = Assignment;
+
+    fn next(&mut self) -> Option<Assignment> {
+        if self.done {
+            return None;
+        }
+        let Some(model) = self.solver.solve() else {
+            self.done = true;
+            return None;
+        };
+        let len = self.variables.len();
+        let blocking = (0..len)
+            .map(|v| Lit::new(v, !model[v]))
+            .collect::<Vec<Lit>>();
+        self.solver.add_clause(&blocking);
+        // Without variables the only model is the empty assignment
+        self.done = len == 0;
+        Some(Assignment::from_values(&self.variables, &model[..len]))
+    }
+}
+
+/// Counts the models of a CNF by splitting it into independent components
+/// and branching on a variable of each, caching the count of every
+/// component met.
+struct Counter {
+    // The count of each component, keyed by its sorted clauses
+    cache: HashMap<Vec<Vec<Lit>>, u128>,
+}
+
+// The clauses left once `lit` is true, or None if one of them becomes empty
+fn assign(clauses: &[Vec<Lit>], lit: Lit) -> Option<Vec<Vec<Lit>>> {
+    let mut residual = Vec::with_capacity(clauses.len());
+    for clause in clauses {
+        if clause.contains(&lit) {
+            continue;
+        }
+        let reduced = clause
+            .iter()
+            .copied()
+            .filter(|&other| other != !lit)
+            .collect::<Vec<Lit>>();
+        if reduced.is_empty() {
+            return None;
+        }
+        residual.push(reduced);
+    }
+    Some(residual)
+}
+
+// The clauses grouped by the connected components of the graph linking the
+// variables that share a clause
+fn components(clauses: Vec<Vec<Lit>>, len: usize) -> Vec<Vec<Vec<Lit>>> {
+    let mut parent = (0..len).collect::<Vec<usize>>();
+    fn find(parent: &mut [usize], mut var: usize) -> usize {
+        while parent[var] != var {
+            parent[var] = parent[parent[var]];
+            var = parent[var];
+        }
+        var
+    }
+    for clause in &clauses {
+        let root = find(&mut parent, clause[0].var());
+        for lit in &clause[1..] {
+            let other = find(&mut parent, lit.var());
+            parent[other] = root;
+        }
+    }
+    let mut groups: HashMap<usize, Vec<Vec<Lit>>> = HashMap::new();
+    for clause in clauses {
+        let root = find(&mut parent, clause[0].var());
+        groups.entry(root).or_default().push(clause);
+    }
+    groups.into_values().collect()
+}
+
+fn variables_of(clauses: &[Vec<Lit>]) -> Vec<usize> {
+    let mut variables = clauses
+        .iter()
+        .flatten()
+        .map(|lit| lit.var())
+        .collect::<Vec<usize>>();
+    variables.sort();
+    variables.dedup();
+    variables
+}
+
+impl Counter {
+    // The number of assignments of `scope` variables satisfying the
+    // clauses, whose variables all belong to `scope`, or None if it does not
+    // fit in a u128
+    fn count(&mut self, mut clauses: Vec<Vec<Lit>>, scope: usize, len: usize) -> Option<u128> {
+        let mut assigned = 0;
+        while let Some(unit) = clauses.iter().find(|clause| clause.len() == 1) {
+            let lit = unit[0];
+            match assign(&clauses, lit) {
+                Some(residual) => clauses = residual,
+                None => return Some(0),
+            }
+            assigned += 1;
+        }
+        let free = scope - assigned - variables_of(&clauses).len();
+        let mut count = 1u128;
+        for component in components(clauses, len) {
+            count = count.checked_mul(self.count_component(component, len)?)?;
+            if count == 0 {
+                return Some(0);
+            }
+        }
+        count.checked_mul(1u128.checked_shl(u32::try_from(free).ok()?)?)
+    }
+
+    fn count_component(&mut self, mut clauses: Vec<Vec<Lit>>, len: usize) -> Option<u128> {
+        for clause in &mut clauses {
+            clause.sort();
+        }
+        clauses.sort();
+        if let Some(&count) = self.cache.get(&clauses) {
+            return Some(count);
+        }
+        // Branch on the variable occurring in the most clauses
+        let mut occurrences = HashMap::new();
+        for lit in clauses.iter().flatten() {
+            *occurrences.entry(lit.var()).or_insert(0) += 1;
+        }
+        let scope = occurrences.len() - 1;
+        let var = *occurrences
+            .iter()
+            .max_by_key(|&(var, count)| (count, std::cmp::Reverse(var)))
+            .unwrap()
+            .0;
+        let mut count = 0u128;
+        for lit in [Lit::positive(var), Lit::negative(var)] {
+            if let Some(residual) = assign(&clauses, lit) {
+                count = count.checked_add(self.count(residual, scope, len)?)?;
+            }
+        }
+        self.cache.insert(clauses, count);
+        Some(count)
+    }
+}
+
+// The number of models of the CNF, or None if it does not fit in a u128
+fn count_models(cnf: &Cnf) -> Option<u128> {
+    let len = cnf.variables.len();
+    let clauses = cnf
+        .iter()
+        .map(|clause| {
+            let mut lits = clause.lits().to_vec();
+            lits.sort();
+            lits.dedup();
+            lits
+        })
+        .filter(|lits| !lits.windows(2).any(|pair| pair[1] == !pair[0]))
+        .collect::<Vec<Vec<Lit>>>();
+    if clauses.iter().any(|clause| clause.is_empty()) {
+        return Some(0);
+    }
+    let mut counter = Counter {
+        cache: HashMap::new(),
+    };
+    counter.count(clauses, len, len)
+}
+
+/// The number of assignments of the variables of the CNF under which it
+/// is true, or `None` if there are 2^128 of them or more.
+pub fn count(cnf: &Cnf) -> Option<u128> {
+    count_models(cnf)
+}
+
+/// The number of models of the formula, counted on its Tseitin encoding
+/// whose auxiliary variables follow from the others, or `None` if there are
+/// 2^128 of them or more.
+pub fn count_formula(formula: &Formula) -> Option<u128> {
+    count_models(&encode(formula, Encoding::Full))
+}
@@ -0,0 +1,109 @@
+use crate::cdcl::Solver;
+use crate::cnf::{Clause, Cnf, Lit};
+use crate::formula::Formula;
+use crate::tseitin::{unique_prefix, Encoding};
+use std::collections::HashMap;
+
+// The CNF of the constraints, in which every clause of the constraint `i`
+// is disabled unless the selector returned at index `i` is true
+fn encode(constraints: &[Formula]) -> (Cnf, Vec<Lit>) {
+    let mut variables = Vec::new();
+    for constraint in constraints {
+        for name in constraint.variables() {
+            if !variables.contains(&name) {
+                variables.push(name);
+            }
+        }
+    }
+    let auxiliary = unique_prefix(&variables, "_t");
+    let selector = unique_prefix(&variables, "_s");
+    let indices = variables
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.clone(), i))
+        .collect::<HashMap<String, usize>>();
+    let mut cnf = Cnf::new(variables);
+    let fresh = |cnf: &mut Cnf, name: String| {
+        cnf.variables.push(name);
+        cnf.variables.len() - 1
+    };
+    let mut auxiliaries = 0;
+    let mut selectors = Vec::new();
+    for (i, constraint) in constraints.iter().enumerate() {
+        let encoded = constraint.tseitin(Encoding::Polarity);
+        // The auxiliary variables of each constraint, which come last, are
+        // renamed apart
+        let first = encoded.cnf.variables.len() - encoded.auxiliaries.len();
+        let mapping = encoded
+            .cnf
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(v, name)| match v < first {
+                true => indices[name],
+                false => {
+                    auxiliaries += 1;
+                    fresh(&mut cnf, format!("{}{}", auxiliary, auxiliaries - 1))
+                }
+            })
+            .collect::<Vec<usize>>();
+        let select = Lit::positive(fresh(&mut cnf, format!("{}{}", selector, i)));
+        selectors.push(select);
+        for clause in encoded.cnf.iter() {
+            let lits = clause
+                .iter()
+                .map(|lit| Lit::new(mapping[lit.var()], lit.is_positive()))
+                .chain([!select])
+                .collect::<Clause>();
+            cnf.push(lits);
+        }
+    }
+    (cnf, selectors)
+}
+
+/// The indices of a minimal unsatisfiable subset of the constraints, in
+/// increasing order, or `None` if their conjunction is satisfiable.
+///
+/// Each constraint is guarded by a selector variable, and the CDCL solver is
+/// asked for a model under the assumption that some selectors are true. The
+/// subset starts from the assumptions involved in the first conflict, then
+/// every constraint is dropped in turn and put back only if the rest becomes
+/// satisfiable, the subset shrinking to the failed assumptions of every
+/// unsatisfiable answer. Removing any constraint of the result makes it
+/// satisfiable, but a smaller unsatisfiable subset may exist.
+pub fn minimal_unsatisfiable_subset(constraints: &[Formula]) -> Option<Vec<usize>> {
+    let (cnf, selectors) = encode(constraints);
+    let mut solver = Solver::new(&cnf);
+    if solver.solve_with(&selectors).is_some() {
+        return None;
+    }
+    let index = |lit: &Lit| selectors.iter().position(|s| s == lit).unwrap();
+    let mut pending = solver
+        .failed_assumptions()
+        .iter()
+        .map(index)
+        .collect::<Vec<usize>>();
+    let mut necessary = Vec::new();
+    while let Some(candidate) = pending.pop() {
+        let assumptions = necessary
+            .iter()
+            .chain(&pending)
+            .map(|&i| selectors[i])
+            .collect::<Vec<Lit>>();
+        if solver.solve_with(&assumptions).is_some() {
+            necessary.push(candidate);
+        } else {
+            // The necessary constraints are part of every conflict left
+            let failed = solver
+                .failed_assumptions()
+                .iter()
+                .map(index)
+                .collect::<Vec<usize>>();
+            pending.retain(|i| failed.contains(i));
+            // The constraint is left out of every later call
+            solver.add_clause(&[!selectors[candidate]]);
+        }
+    }
+    necessary.sort();
+    Some(necessary)
+}
@@ -1,7 +1,16 @@
+use crate::assignment::Assignment;
+use crate::cnf::{Clause, Cnf, Lit};
+use crate::dpll;
+use crate::espresso;
+use crate::formula::Formula;
 use crate::kmap::KMap;
-use crate::permutationlist::PermutationList;
+use crate::models::{self, Models};
+use crate::mus;
+use crate::petrick::Cost;
+use crate::quinemccluskey::{self, Implicant};
 use crate::truthtable::TruthTable;
-use anyhow::{anyhow, Context, Result};
+use crate::tseitin::Encoding;
+use anyhow::{bail, Context, Result};
 use std::collections::{HashMap, HashSet};
 
 pub enum Set {
@@ -9,259 +18,250 @@ pub enum Set {
     Negation(HashSet<i32>),
 }
 
-pub struct Parser {
-    pub result: Option<bool>,
+/// How [`Parser::evaluate_cnf_with`] and [`Parser::evaluate_dnf_with`] choose
+/// the clauses or terms of the formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Minimiser {
+    /// A minimum set of prime implicants or implicates, from a Karnaugh map for up to four
+    /// variables and from Quine–McCluskey and Petrick's method beyond.
+    #[default]
+    Exact,
+    /// A small set of implicants or implicates found by the Espresso heuristic, for
+    /// functions too large for an exact cover.
+    Espresso,
 }
 
+/// The entry points of the exercises, taking formulas in reverse polish notation.
+///
+/// A parser holds no state, so a single one can be shared between threads.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Parser;
+
 impl Parser {
     pub fn new() -> Self {
-        Parser { result: None }
+        Parser
     }
 
-    pub fn evaluate(&mut self, formula: &str) -> Result<()> {
-        let mut lexer = formula.chars();
-        let mut stack = Vec::new();
-        while let Some(c) = lexer.next() {
-            match c {
-                '0' => stack.push(false),
-                '1' => stack.push(true),
-                '!' => {
-                    let child = stack.pop().context("Unexpected end of formula")?;
-                    stack.push(!child);
-                }
-                '&' | '|' | '^' | '>' | '=' => {
-                    let (rhs, lhs) = (
-                        stack.pop().context("Unexpected end of formula")?,
-                        stack.pop().context("Unexpected end of formula")?,
-                    );
-                    stack.push(match c {
-                        '&' => lhs & rhs,
-                        '=' => lhs == rhs,
-                        '>' => !lhs | rhs,
-                        '|' => lhs | rhs,
-                        '^' => lhs ^ rhs,
-                        _ => false,
-                    });
-                }
-                _ => break,
-            }
-        }
-        self.result = stack.pop();
-        Ok(())
+    pub fn evaluate(&self, formula: &str) -> Result<bool> {
+        Formula::parse_rpn(formula)?.evaluate()
     }
 
-    pub fn truth_table_from(&mut self, formula: &str) -> Result<TruthTable> {
-        let mut table = TruthTable::new();
-        let mut permutationlist = PermutationList::new(formula);
-        while let Some(permutation) = permutationlist.next() {
-            self.evaluate(&permutation)?;
-            table.results.push(self.result.unwrap());
-        }
-        table.variables.append(&mut permutationlist.variables);
-        Ok(table)
+    pub fn truth_table_from(&self, formula: &str) -> Result<TruthTable> {
+        TruthTable::from_formula(&Formula::parse_rpn(formula)?)
     }
 
-    fn get_child(&mut self, stack: &mut Vec<char>) -> Vec<char> {
-        let mut child = Vec::new();
-        let mut count = 0;
-        while let Some(c) = stack.pop() {
-            count += match c {
-                '|' | '&' => 1,
-                '!' => 0,
-                _ => -1,
-            };
-            child.push(c);
-            if count < 0 {
-                break;
-            }
-        }
-        child
+    pub fn evaluate_nnf(&self, formula: &str) -> Result<String> {
+        Ok(Formula::parse_rpn(formula)?.to_nnf().to_rpn())
     }
 
-    fn append(&mut self, stack: &mut Vec<char>, append: &mut Vec<char>) {
-        while let Some(c) = append.pop() {
-            match c {
-                'A'..='Z' => {
-                    stack.push(c);
-                    match append.pop() {
-                        Some('!') => (),
-                        Some(c) => {
-                            stack.push('!');
-                            append.push(c);
-                        }
-                        None => stack.push('!'),
-                    }
-                }
-                '&' => stack.push('|'),
-                '|' => stack.push('&'),
-                _ => (),
-            }
-        }
+    pub fn evaluate_cnf(&self, formula: &str) -> Result<Cnf> {
+        self.evaluate_cnf_with(formula, Minimiser::Exact)
     }
 
-    fn apply_de_morgan(&mut self, stack: &mut Vec<char>) {
-        let c = stack.pop().unwrap();
-        if c == '&' || c == '|' {
-            let mut children = self.get_child(stack);
-            children.append(&mut self.get_child(stack));
-            self.append(stack, &mut children);
-            stack.push(if c == '&' { '|' } else { '&' });
-        } else if c != '!' {
-            stack.push(c);
-            stack.push('!');
+    pub fn evaluate_cnf_with(&self, formula: &str, strategy: Minimiser) -> Result<Cnf> {
+        let table = self.truth_table_from(formula)?;
+        Ok(self.cnf_from_table(&table, strategy))
+    }
+
+    /// An equivalent formula in CNF obtained syntactically, by distributing
+    /// the disjunctions of the negation normal form, which fails when it
+    /// needs more than `max_clauses` clauses.
+    pub fn evaluate_cnf_distributed(&self, formula: &str, max_clauses: usize) -> Result<Cnf> {
+        Ok(Formula::parse_rpn(formula)?.distribute_cnf(max_clauses)?)
+    }
+
+    /// A formula in CNF that agrees with `table` on every row that is not
+    /// a don't care.
+    pub fn cnf_from_table(&self, table: &TruthTable, strategy: Minimiser) -> Cnf {
+        if table.variables.is_empty() {
+            return Cnf::constant(table.get(0));
+        }
+        let len = table.variables.len();
+        let clauses = Self::minimise(table, false, strategy)
+            .iter()
+            .map(|implicate| {
+                implicate
+                    .literals(len)
+                    .map(|(b, value)| Lit::new(b, !value))
+                    .collect::<Clause>()
+            })
+            .collect();
+        Cnf {
+            variables: table.variables.clone(),
+            clauses,
         }
     }
 
-    fn rewrite_exclusive_or(&mut self, stack: &mut Vec<char>) {
-        let (mut right, mut left) = (self.get_child(stack), self.get_child(stack));
-        right.reverse();
-        left.reverse();
-        let (mut right_clone, mut left_clone) = (right.clone(), left.clone());
-        stack.append(&mut left);
-        stack.append(&mut right);
-        stack.push('|');
-        stack.append(&mut left_clone);
-        stack.append(&mut right_clone);
-        stack.push('&');
-        self.apply_de_morgan(stack);
-        stack.push('&');
+    pub fn evaluate_dnf(&self, formula: &str) -> Result<String> {
+        self.evaluate_dnf_with(formula, Minimiser::Exact)
     }
 
-    fn rewrite_material_condition(&mut self, stack: &mut Vec<char>) {
-        let (mut right, mut left) = (self.get_child(stack), self.get_child(stack));
-        self.append(stack, &mut left);
-        right.reverse();
-        stack.append(&mut right);
-        stack.push('|');
+    pub fn evaluate_dnf_with(&self, formula: &str, strategy: Minimiser) -> Result<String> {
+        let table = self.truth_table_from(formula)?;
+        Ok(self.dnf_from_table(&table, strategy).to_rpn())
     }
 
-    fn rewrite_equivalence(&mut self, stack: &mut Vec<char>) {
-        let (mut right, mut left) = (self.get_child(stack), self.get_child(stack));
-        right.reverse();
-        left.reverse();
-        let (mut right_clone, mut left_clone) = (right.clone(), left.clone());
-        left.append(&mut right_clone);
-        right.append(&mut left_clone);
-        self.rewrite_material_condition(&mut left);
-        self.rewrite_material_condition(&mut right);
-        stack.append(&mut left);
-        stack.append(&mut right);
-        stack.push('&');
+    /// A formula in DNF that agrees with `table` on every row that is not
+    /// a don't care.
+    pub fn dnf_from_table(&self, table: &TruthTable, strategy: Minimiser) -> Formula {
+        if table.variables.is_empty() {
+            return Formula::Const(table.get(0));
+        }
+        let terms = Self::minimise(table, true, strategy)
+            .iter()
+            .map(|implicant| implicant.to_term(&table.variables))
+            .collect::<Vec<Formula>>();
+        Formula::disjunction(terms)
     }
 
-    pub fn evaluate_nnf(&mut self, formula: &str) -> Result<String> {
-        let mut lexer = formula.chars();
-        let mut stack = Vec::new();
-        while let Some(c) = lexer.next() {
-            match c {
-                'A'..='Z' | '&' | '|' => stack.push(c),
-                '!' => self.apply_de_morgan(&mut stack),
-                '^' => self.rewrite_exclusive_or(&mut stack),
-                '>' => self.rewrite_material_condition(&mut stack),
-                '=' => self.rewrite_equivalence(&mut stack),
-                _ => break,
+    // A cover of the rows of `table` whose result is `value`
+    fn minimise(table: &TruthTable, value: bool, strategy: Minimiser) -> Vec<Implicant> {
+        let len = table.variables.len();
+        match strategy {
+            // The Karnaugh map groups the false cells
+            Minimiser::Exact if len <= 4 => {
+                let mut kmap = match value {
+                    true => KMap::from(&table.complement()),
+                    false => KMap::from(table),
+                };
+                kmap.get_cover().unwrap_or_default()
+            }
+            Minimiser::Exact => {
+                let rows = match value {
+                    true => table.ones().collect::<Vec<usize>>(),
+                    false => table.zeros().collect::<Vec<usize>>(),
+                };
+                let dont_cares = table.dont_cares().collect::<Vec<usize>>();
+                quinemccluskey::minimise(len, &rows, &dont_cares, Cost::Terms)
             }
+            Minimiser::Espresso => espresso::minimise(table, value),
         }
-        Ok(String::from_iter(stack))
     }
 
-    pub fn evaluate_cnf(&mut self, formula: &str) -> Result<String> {
-        let mut form = String::new();
-        if let Ok(table) = self.truth_table_from(formula) {
-            let mut kmap = KMap::from(table);
+    /// Whether some assignment makes the formula true, decided by the DPLL
+    /// solver on its Plaisted–Greenbaum encoding.
+    pub fn is_satisfiable(&self, formula: &str) -> bool {
+        self.find_model(formula).is_some()
+    }
 
-            if let Some(minterms) = kmap.get_minterms() {
-                for term in &minterms {
-                    let mut tmp = vec![0, 0, 0, 0];
-                    let mut count = 0;
-                    for b in &term.0 {
-                        for bit in 0..(kmap.variables.len()) {
-                            if b & (1 << ((kmap.variables.len() - 1) - bit)) != 0 {
-                                tmp[bit] += 1;
-                            }
-                        }
-                    }
-                    for (i, v) in kmap.variables.iter().enumerate() {
-                        if tmp[i] == 0 {
-                            form.push(*v);
-                            count += 1;
-                        } else if tmp[i] == term.len() {
-                            form.push(*v);
-                            form.push('!');
-                            count += 1;
-                        }
-                    }
-                    if count > 1 {
-                        form.push_str(&"|".repeat(count - 1));
-                    }
-                }
-                form.push_str(&"&".repeat(minterms.len() - 1));
-            }
-        }
+    /// An assignment of every variable of the formula under which it is
+    /// true, or `None` if there is none or the formula is invalid.
+    pub fn find_model(&self, formula: &str) -> Option<Assignment> {
+        let formula = Formula::parse_rpn(formula).ok()?;
+        let cnf = formula.tseitin(Encoding::Polarity).cnf;
+        let model = dpll::solve(&cnf)?;
+        // The auxiliary variables come after the variables of the formula,
+        // some of which may have been folded away with the constants
+        let values = cnf
+            .variables
+            .iter()
+            .zip(model)
+            .collect::<HashMap<&String, bool>>();
+        let assignment = formula
+            .variables()
+            .into_iter()
+            .map(|name| {
+                let value = values.get(&name).copied().unwrap_or(false);
+                (name, value)
+            })
+            .collect::<Assignment>();
+        debug_assert!(formula.eval(&assignment).unwrap_or(false));
+        Some(assignment)
+    }
 
-        Ok(String::from(form))
+    /// Whether some assignment makes the CNF true, e.g. one read from a
+    /// DIMACS file, decided by the DPLL solver.
+    pub fn is_satisfiable_cnf(&self, cnf: &Cnf) -> bool {
+        dpll::is_satisfiable(cnf)
     }
 
-    pub fn is_satisfiable(&mut self, formula: &str) -> bool {
-        let mut permutationlist = PermutationList::new(formula);
-        while let Some(permutation) = permutationlist.next() {
-            if self.evaluate(&permutation).is_ok() {
-                if self.result.unwrap() {
-                    return true;
-                }
-            }
+    /// The truth table of the CNF over the variables occurring in it.
+    pub fn truth_table_from_cnf(&self, cnf: &Cnf) -> Result<TruthTable> {
+        TruthTable::from_formula(&cnf.to_formula())
+    }
+
+    /// Every model of the formula, found lazily.
+    pub fn models(&self, formula: &str) -> Result<Models> {
+        Ok(Models::new(&Formula::parse_rpn(formula)?))
+    }
+
+    /// The number of models of the formula, counted without listing them.
+    ///
+    /// Fails when the formula has 2^128 models or more, which only formulas
+    /// with at least 128 variables can have.
+    pub fn count_models(&self, formula: &str) -> Result<u128> {
+        let formula = Formula::parse_rpn(formula)?;
+        match models::count_formula(&formula) {
+            Some(count) => Ok(count),
+            None => bail!(
+                "The formula over {} variables has too many models to count in a u128",
+                formula.variables().len()
+            ),
         }
-        false
     }
 
-    pub fn evaluate_set(&mut self, formula: &str, sets: &[&[i32]]) -> Result<Vec<i32>> {
-        let formula = self.evaluate_nnf(formula.clone())?;
-        if let Ok(table) = self.truth_table_from(&formula) {
-            let mut lexer = formula.chars();
-            let u: HashSet<i32> = (sets
-                .iter()
-                .flat_map(|s| s.iter().cloned())
-                .collect::<Vec<i32>>())
+    /// The names of a minimal unsatisfiable subset of the named
+    /// constraints, in the order given, or `None` if they can all hold at
+    /// once.
+    pub fn unsatisfiable_core(&self, constraints: &[(&str, &str)]) -> Result<Option<Vec<String>>> {
+        let formulas = constraints
             .iter()
-            .cloned()
-            .collect::<HashSet<i32>>();
-            let map: HashMap<char, &&[i32]> =
-                table.variables.into_iter().zip(sets.iter()).collect();
-            let mut stack: Vec<HashSet<i32>> = Vec::new();
-            while let Some(c) = lexer.next() {
-                match c {
-                    'A'..='Z' => {
-                        stack.push(HashSet::from_iter(
-                            (map.get(&c).unwrap())
-                                .iter()
-                                .cloned()
-                                .collect::<HashSet<i32>>(),
-                        ));
-                    }
-                    '!' => {
-                        let tmp = stack.pop().unwrap();
-                        stack.push(u.difference(&tmp).cloned().collect());
-                    }
-                    '&' | '|' => {
-                        let (rhs, lhs) = (
-                            stack.pop().context("Unexpected end of formula")?,
-                            stack.pop().context("Unexpected end of formula")?,
-                        );
-                        if c == '&' {
-                            stack.push(lhs.intersection(&rhs).cloned().collect());
-                        } else if c == '|' {
-                            stack.push(lhs.union(&rhs).cloned().collect());
-                        }
-                    }
-                    _ => (),
-                }
-            }
-            if let Some(res) = stack.pop() {
-                let mut v = Vec::from_iter(res);
-                v.sort();
-                return Ok(v);
-            }
-        }
-        Err(anyhow!("Invalid formula!"))
+            .map(|(name, formula)| {
+                Formula::parse_rpn(formula)
+                    .with_context(|| format!("Invalid constraint '{}'", name))
+            })
+            .collect::<Result<Vec<Formula>>>()?;
+        let core = mus::minimal_unsatisfiable_subset(&formulas);
+        Ok(core.map(|indices| {
+            indices
+                .into_iter()
+                .map(|i| String::from(constraints[i].0))
+                .collect()
+        }))
+    }
+
+    pub fn evaluate_set(&self, formula: &str, sets: &[&[i32]]) -> Result<Vec<i32>> {
+        let formula = Formula::parse_rpn(formula)?;
+        let u: HashSet<i32> = sets.iter().flat_map(|s| s.iter().cloned()).collect();
+        let map: HashMap<String, HashSet<i32>> = formula
+            .variables()
+            .into_iter()
+            .zip(sets.iter().map(|s| s.iter().cloned().collect()))
+            .collect();
+        let mut v = Vec::from_iter(Self::evaluate_set_of(&formula, &map, &u)?);
+        v.sort();
+        Ok(v)
+    }
+
+    fn evaluate_set_of(
+        formula: &Formula,
+        map: &HashMap<String, HashSet<i32>>,
+        u: &HashSet<i32>,
+    ) -> Result<HashSet<i32>> {
+        let complement = |s: &HashSet<i32>| u.difference(s).cloned().collect::<HashSet<i32>>();
+        formula.fold(|node, sets: Vec<Result<HashSet<i32>>>| {
+            let sets = sets.into_iter().collect::<Result<Vec<_>>>()?;
+            Ok(match node {
+                Formula::Var(name) => map
+                    .get(name)
+                    .cloned()
+                    .with_context(|| format!("No set given for variable '{}'", name))?,
+                Formula::Const(true) => u.clone(),
+                Formula::Const(false) => HashSet::new(),
+                Formula::Not(_) => complement(&sets[0]),
+                Formula::And(..) => &sets[0] & &sets[1],
+                Formula::Or(..) => &sets[0] | &sets[1],
+                Formula::Xor(..) => &sets[0] ^ &sets[1],
+                Formula::Imply(..) => &complement(&sets[0]) | &sets[1],
+                Formula::Equiv(..) => complement(&(&sets[0] ^ &sets[1])),
+                _ => u
+                    .iter()
+                    .filter(|x| {
+                        node.truth_function(&sets.iter().map(|s| s.contains(x)).collect::<Vec<_>>())
+                    })
+                    .cloned()
+                    .collect(),
+            })
+        })
     }
 }
@@ -0,0 +1,157 @@
+use crate::quinemccluskey::Implicant;
+use std::collections::BTreeSet;
+
+/// What a cover of the rows of a truth table costs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cost {
+    /// The number of implicants, i.e. of terms or clauses in the formula.
+    #[default]
+    Terms,
+    /// The total number of literals of the implicants.
+    Literals,
+}
+
+impl Cost {
+    pub fn of(self, len: usize, implicants: &[Implicant]) -> usize {
+        match self {
+            Cost::Terms => implicants.len(),
+            Cost::Literals => implicants.iter().map(|i| i.literal_count(len)).sum(),
+        }
+    }
+}
+
+/// The implicants that are the only ones covering one of `rows`.
+pub fn essential_implicants(implicants: &[Implicant], rows: &[usize]) -> Vec<Implicant> {
+    let mut essentials = BTreeSet::new();
+    for &row in rows {
+        let mut covering = implicants.iter().filter(|i| i.covers(row));
+        if let (Some(implicant), None) = (covering.next(), covering.next()) {
+            essentials.insert(*implicant);
+        }
+    }
+    essentials.into_iter().collect()
+}
+
+/// A cover of `rows` built greedily from `implicants`, starting from the
+/// essential ones and then taking those covering the most remaining rows at
+/// the lowest cost. `None` if some row is covered by no implicant.
+pub fn greedy_cover(
+    len: usize,
+    implicants: &[Implicant],
+    rows: &[usize],
+    cost: Cost,
+) -> Option<Vec<Implicant>> {
+    let mut cover = essential_implicants(implicants, rows);
+    let mut uncovered = uncovered_rows(&cover, rows);
+    while !uncovered.is_empty() {
+        let best = *implicants
+            .iter()
+            .filter(|i| uncovered.iter().any(|row| i.covers(*row)))
+            .max_by_key(|i| {
+                let count = uncovered.iter().filter(|row| i.covers(**row)).count();
+                (count, std::cmp::Reverse(cost.of(len, &[**i])))
+            })?;
+        uncovered.retain(|row| !best.covers(*row));
+        cover.push(best);
+    }
+    cover.sort();
+    Some(cover)
+}
+
+/// All the covers of `rows` by `implicants` of minimum `cost`, found by
+/// taking the essential implicants and then expanding Petrick's product of
+/// sums for the remaining rows, with terms dearer than a greedy cover pruned.
+///
+/// The covers are sorted by cost under the other metric, then by implicants.
+/// Empty if some row is covered by no implicant.
+pub fn minimum_covers(
+    len: usize,
+    implicants: &[Implicant],
+    rows: &[usize],
+    cost: Cost,
+) -> Vec<Vec<Implicant>> {
+    let bound = match greedy_cover(len, implicants, rows, cost) {
+        Some(cover) => cost.of(len, &cover),
+        None => return Vec::new(),
+    };
+    let essentials = essential_implicants(implicants, rows);
+    let candidates = implicants
+        .iter()
+        .filter(|i| !essentials.contains(i))
+        .copied()
+        .collect::<Vec<Implicant>>();
+    let essential_cost = cost.of(len, &essentials);
+    let cost_of = |product: &BTreeSet<usize>| {
+        let chosen = product.iter().map(|i| candidates[*i]).collect::<Vec<_>>();
+        essential_cost + cost.of(len, &chosen)
+    };
+
+    // Each sum holds the candidates covering one row, and a product is
+    // a set of candidates covering every row of the sums multiplied so far
+    let mut sums = uncovered_rows(&essentials, rows)
+        .into_iter()
+        .map(|row| {
+            (0..candidates.len())
+                .filter(|i| candidates[*i].covers(row))
+                .collect::<BTreeSet<usize>>()
+        })
+        .collect::<Vec<_>>();
+    sums.sort_by_key(|sum| sum.len());
+    sums.dedup();
+    let mut products = vec![BTreeSet::new()];
+    for sum in &sums {
+        let mut next = Vec::new();
+        for product in products {
+            if !product.is_disjoint(sum) {
+                next.push(product);
+                continue;
+            }
+            for i in sum {
+                let mut extended = product.clone();
+                extended.insert(*i);
+                if cost_of(&extended) <= bound {
+                    next.push(extended);
+                }
+            }
+        }
+        products = absorb(next);
+    }
+
+    let minimum = products.iter().map(cost_of).min();
+    let other = match cost {
+        Cost::Terms => Cost::Literals,
+        Cost::Literals => Cost::Terms,
+    };
+    let mut covers = products
+        .iter()
+        .filter(|product| Some(cost_of(product)) == minimum)
+        .map(|product| {
+            let mut cover = essentials.clone();
+            cover.extend(product.iter().map(|i| candidates[*i]));
+            cover.sort();
+            cover
+        })
+        .collect::<Vec<_>>();
+    covers.sort_by_cached_key(|cover| (other.of(len, cover), cover.clone()));
+    covers
+}
+
+fn uncovered_rows(cover: &[Implicant], rows: &[usize]) -> Vec<usize> {
+    rows.iter()
+        .copied()
+        .filter(|row| !cover.iter().any(|i| i.covers(*row)))
+        .collect()
+}
+
+// Removes the duplicate products and those containing another one
+fn absorb(mut products: Vec<BTreeSet<usize>>) -> Vec<BTreeSet<usize>> {
+    products.sort_by_key(|product| product.len());
+    products.dedup();
+    let mut kept: Vec<BTreeSet<usize>> = Vec::new();
+    for product in products {
+        if !kept.iter().any(|k| k.is_subset(&product)) {
+            kept.push(product);
+        }
+    }
+    kept
+}
@@ -0,0 +1,139 @@
+use crate::formula::Formula;
+use crate::petrick::{self, Cost};
+use std::collections::{BTreeSet, HashSet};
+
+/// A product term over the rows of a truth table: the rows whose index equals
+/// `value` on every bit outside of `mask`.
+///
+/// Bit `len - 1 - b` of a row index holds the value of variable `b`, as in
+/// [`TruthTable`](crate::truthtable::TruthTable).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Implicant {
+    pub value: u32,
+    pub mask: u32,
+}
+
+impl Implicant {
+    pub fn minterm(row: usize) -> Self {
+        Implicant {
+            value: row as u32,
+            mask: 0,
+        }
+    }
+
+    pub fn covers(&self, row: usize) -> bool {
+        row as u32 & !self.mask == self.value
+    }
+
+    /// The covered rows, in increasing order.
+    pub fn rows(&self) -> impl Iterator<Item = usize> {
+        let (value, mask) = (self.value, self.mask);
+        let mut subset = Some(0u32);
+        std::iter::from_fn(move || {
+            let current = subset?;
+            let next = current.wrapping_sub(mask) & mask;
+            subset = if next == 0 { None } else { Some(next) };
+            Some((value | current) as usize)
+        })
+    }
+
+    /// The number of covered rows.
+    pub fn size(&self) -> usize {
+        1 << self.mask.count_ones()
+    }
+
+    /// Whether every row covered by `other` is also covered by `self`.
+    pub fn contains(&self, other: &Implicant) -> bool {
+        other.mask & !self.mask == 0 && other.value & !self.mask == self.value
+    }
+
+    /// The number of variables fixed by the implicant among `len` variables.
+    pub fn literal_count(&self, len: usize) -> usize {
+        len - self.mask.count_ones() as usize
+    }
+
+    /// The fixed variables among `len` as `(index, value)` pairs, in index order.
+    pub fn literals(&self, len: usize) -> impl Iterator<Item = (usize, bool)> + '_ {
+        (0..len)
+            .map(move |b| (b, 1 << (len - 1 - b)))
+            .filter(|(_, bit)| self.mask & bit == 0)
+            .map(|(b, bit)| (b, self.value & bit != 0))
+    }
+
+    /// The conjunction of the fixed variables, true exactly on the covered rows.
+    pub fn to_term(&self, variables: &[String]) -> Formula {
+        Formula::conjunction(self.literals(variables.len()).map(|(b, value)| {
+            let v = Formula::var(&variables[b]);
+            if value {
+                v
+            } else {
+                !v
+            }
+        }))
+    }
+
+    /// The disjunction of the negated fixed variables, false exactly on the covered rows.
+    pub fn to_clause(&self, variables: &[String]) -> Formula {
+        Formula::disjunction(self.literals(variables.len()).map(|(b, value)| {
+            let v = Formula::var(&variables[b]);
+            if value {
+                !v
+            } else {
+                v
+            }
+        }))
+    }
+}
+
+/// The prime implicants of the function over `len` variables that is true on
+/// `minterms`, found by repeatedly merging implicants that differ in one bit.
+pub fn prime_implicants(len: usize, minterms: &[usize]) -> Vec<Implicant> {
+    let mut current = minterms
+        .iter()
+        .map(|&row| Implicant::minterm(row))
+        .collect::<HashSet<Implicant>>();
+    let mut primes = BTreeSet::new();
+    while !current.is_empty() {
+        let mut next = HashSet::new();
+        let mut merged = HashSet::new();
+        for implicant in &current {
+            for bit in (0..len).map(|b| 1u32 << b) {
+                if (implicant.mask | implicant.value) & bit != 0 {
+                    continue;
+                }
+                let pair = Implicant {
+                    value: implicant.value | bit,
+                    mask: implicant.mask,
+                };
+                if current.contains(&pair) {
+                    merged.insert(*implicant);
+                    merged.insert(pair);
+                    next.insert(Implicant {
+                        value: implicant.value,
+                        mask: implicant.mask | bit,
+                    });
+                }
+            }
+        }
+        primes.extend(current.difference(&merged).copied());
+        current = next;
+    }
+    primes.into_iter().collect()
+}
+
+/// A cover of `minterms` by prime implicants of minimum `cost`, which may
+/// also cover any of the `dont_cares` rows.
+pub fn minimise(
+    len: usize,
+    minterms: &[usize],
+    dont_cares: &[usize],
+    cost: Cost,
+) -> Vec<Implicant> {
+    let rows = minterms
+        .iter()
+        .chain(dont_cares)
+        .copied()
+        .collect::<Vec<usize>>();
+    let primes = prime_implicants(len, &rows);
+    petrick::minimum_covers(len, &primes, minterms, cost).swap_remove(0)
+}
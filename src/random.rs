@@ -0,0 +1,19 @@
+/// A xorshift generator, so that random instances in tests and benchmarks
+/// are the same on every run.
+pub struct Random(u64);
+
+impl Random {
+    /// A generator seeded with `seed`, which must not be zero, as xorshift
+    /// maps zero to itself.
+    pub fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0, "a xorshift generator needs a nonzero seed");
+        Random(seed)
+    }
+
+    pub fn next_usize(&mut self) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 as usize
+    }
+}
@@ -1,17 +1,412 @@
+use crate::assignment::Assignment;
+use crate::evaluator::Evaluator;
+use crate::formula::Formula;
+use crate::quinemccluskey::Implicant;
+use anyhow::{bail, Context, Result};
 use std::fmt;
 
-#[derive(PartialEq)]
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// The result of a row of a truth table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Output {
+    False,
+    True,
+    /// The row cannot occur, so either result is acceptable.
+    DontCare,
+}
+
+impl From<bool> for Output {
+    fn from(value: bool) -> Self {
+        if value {
+            Output::True
+        } else {
+            Output::False
+        }
+    }
+}
+
+impl fmt::Display for Output {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Output::False => write!(f, "0"),
+            Output::True => write!(f, "1"),
+            Output::DontCare => write!(f, "X"),
+        }
+    }
+}
+
+/// The truth table of a function over `variables`, one bit per row, with
+/// a second bit per row marking the rows whose result does not matter.
+///
+/// Row `i` assigns to `variables[b]` the bit `len - 1 - b` of `i`, so the first
+/// variable is the most significant one, as in a written-out truth table.
+#[derive(Clone, Eq)]
 pub struct TruthTable {
-    pub variables: Vec<char>,
-    pub results: Vec<bool>,
+    pub variables: Vec<String>,
+    words: Vec<u64>,
+    // Empty until a row is set to a don't care, so that tables without
+    // don't cares take one bit per row
+    dont_cares: Vec<u64>,
+}
+
+impl PartialEq for TruthTable {
+    fn eq(&self, other: &Self) -> bool {
+        self.variables == other.variables
+            && self.words == other.words
+            && (0..self.words.len()).all(|w| self.dont_care_word(w) == other.dont_care_word(w))
+    }
 }
 
 impl TruthTable {
-    pub fn new() -> Self {
+    pub const MAX_VARIABLES: usize = 32;
+
+    /// A table over `variables` in which every row is false.
+    ///
+    /// # Panics
+    ///
+    /// When there are more than [`MAX_VARIABLES`](Self::MAX_VARIABLES)
+    /// variables; [`from_sets`](Self::from_sets) returns an error instead.
+    pub fn new(variables: Vec<String>) -> Self {
+        assert!(
+            variables.len() <= Self::MAX_VARIABLES,
+            "a truth table holds at most {} variables",
+            Self::MAX_VARIABLES
+        );
+        let words = (1usize << variables.len()).div_ceil(WORD_BITS);
         TruthTable {
-            variables: Vec::new(),
-            results: Vec::new(),
+            variables,
+            words: vec![0; words],
+            dont_cares: Vec::new(),
+        }
+    }
+
+    // A table over `variables` in which every row is false, or an error if
+    // there are too many of them
+    fn checked(variables: Vec<String>) -> Result<Self> {
+        if variables.len() > Self::MAX_VARIABLES {
+            bail!(
+                "{} variables, a truth table holds at most {}",
+                variables.len(),
+                Self::MAX_VARIABLES
+            );
+        }
+        Ok(TruthTable::new(variables))
+    }
+
+    /// Builds a table that is true on the rows of `on`, don't care on the rows
+    /// of `dont_care` and false everywhere else.
+    pub fn from_sets(
+        variables: Vec<String>,
+        on: impl IntoIterator<Item = usize>,
+        dont_care: impl IntoIterator<Item = usize>,
+    ) -> Result<Self> {
+        let mut table = TruthTable::checked(variables)?;
+        for row in on {
+            table.check_row(row)?;
+            table.set(row, true);
+        }
+        for row in dont_care {
+            table.check_row(row)?;
+            table.set_output(row, Output::DontCare);
+        }
+        Ok(table)
+    }
+
+    fn check_row(&self, row: usize) -> Result<()> {
+        if row >= self.row_count() {
+            bail!(
+                "Index {} out of range for {} variables",
+                row,
+                self.variables.len()
+            );
+        }
+        Ok(())
+    }
+
+    /// Builds a table by calling `f` with the values of the variables of each row.
+    ///
+    /// # Panics
+    ///
+    /// When there are more than [`MAX_VARIABLES`](Self::MAX_VARIABLES)
+    /// variables, as `f` would be called on more than 2^32 rows.
+    pub fn from_fn<F: FnMut(&[bool]) -> bool>(variables: Vec<String>, mut f: F) -> Self {
+        let mut table = TruthTable::new(variables);
+        let mut values = Vec::with_capacity(table.variables.len());
+        for row in 0..table.row_count() {
+            values.clear();
+            values.extend(table.assignment_of(row));
+            if f(&values) {
+                table.set(row, true);
+            }
+        }
+        table
+    }
+
+    /// Builds the table of `formula` over its variables, 64 rows at a time.
+    pub fn from_formula(formula: &Formula) -> Result<Self> {
+        let variables = formula.variables();
+        if variables.len() > Self::MAX_VARIABLES {
+            bail!(
+                "Formula has {} variables, a truth table holds at most {}",
+                variables.len(),
+                Self::MAX_VARIABLES
+            );
+        }
+        let mut table = TruthTable::new(variables);
+        table.words = Evaluator::new(formula).words().collect();
+        Ok(table)
+    }
+
+    pub fn row_count(&self) -> usize {
+        1 << self.variables.len()
+    }
+
+    /// Whether the result of `row` is true, a don't care counting as false.
+    pub fn get(&self, row: usize) -> bool {
+        self.output(row) == Output::True
+    }
+
+    pub fn set(&mut self, row: usize, value: bool) {
+        self.set_output(row, Output::from(value));
+    }
+
+    pub fn output(&self, row: usize) -> Output {
+        assert!(row < self.row_count(), "row {} out of range", row);
+        let (w, bit) = (row / WORD_BITS, row % WORD_BITS);
+        if self.dont_care_word(w) >> bit & 1 == 1 {
+            Output::DontCare
+        } else {
+            Output::from(self.words[w] >> bit & 1 == 1)
+        }
+    }
+
+    pub fn set_output(&mut self, row: usize, output: Output) {
+        assert!(row < self.row_count(), "row {} out of range", row);
+        let (w, bit) = (row / WORD_BITS, 1 << (row % WORD_BITS));
+        self.words[w] &= !bit;
+        if let Some(dont_care) = self.dont_cares.get_mut(w) {
+            *dont_care &= !bit;
+        }
+        match output {
+            Output::False => (),
+            Output::True => self.words[w] |= bit,
+            Output::DontCare => {
+                if self.dont_cares.is_empty() {
+                    self.dont_cares = vec![0; self.words.len()];
+                }
+                self.dont_cares[w] |= bit;
+            }
+        }
+    }
+
+    // The don't care bits of the rows of word `w`
+    fn dont_care_word(&self, w: usize) -> u64 {
+        self.dont_cares.get(w).copied().unwrap_or(0)
+    }
+
+    /// The table with the true and false rows swapped, keeping the don't cares.
+    pub fn complement(&self) -> TruthTable {
+        let mut table = self.clone();
+        for (w, word) in table.words.iter_mut().enumerate() {
+            *word = !*word & !self.dont_care_word(w);
+        }
+        table.clear_padding();
+        table
+    }
+
+    // Clears the bits past the last row of a table of less than 64 rows
+    fn clear_padding(&mut self) {
+        if self.row_count() < WORD_BITS {
+            self.words[0] &= (1 << self.row_count()) - 1;
+        }
+    }
+
+    /// Whether `f` agrees with the table on every row that is not a don't care.
+    pub fn is_implemented_by(&self, f: &TruthTable) -> bool {
+        self.variables == f.variables
+            && self
+                .words
+                .iter()
+                .zip(&f.words)
+                .enumerate()
+                .all(|(w, (word, other))| (word ^ other) & !self.dont_care_word(w) == 0)
+    }
+
+    /// The row in which `variables[b]` has the value `values[b]`.
+    pub fn row_of(&self, values: &[bool]) -> usize {
+        assert_eq!(values.len(), self.variables.len());
+        values.iter().fold(0, |row, &b| row << 1 | b as usize)
+    }
+
+    /// The values of the variables in `row`.
+    pub fn assignment_of(&self, row: usize) -> impl Iterator<Item = bool> {
+        let len = self.variables.len();
+        (0..len).map(move |b| row & (1 << (len - 1 - b)) != 0)
+    }
+
+    /// The result for the row in which `variables[b]` has the value `values[b]`.
+    pub fn lookup(&self, values: &[bool]) -> bool {
+        self.get(self.row_of(values))
+    }
+
+    /// The result for the row given by `assignment`, which must assign every variable.
+    pub fn lookup_assignment(&self, assignment: &Assignment) -> Result<bool> {
+        let values = self
+            .variables
+            .iter()
+            .map(|v| {
+                assignment
+                    .get(v)
+                    .with_context(|| format!("Unassigned variable '{}'", v))
+            })
+            .collect::<Result<Vec<bool>>>()?;
+        Ok(self.lookup(&values))
+    }
+
+    /// The results of all rows in order, a don't care counting as false.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.row_count()).map(|row| self.get(row))
+    }
+
+    /// The outputs of all rows in order.
+    pub fn outputs(&self) -> impl Iterator<Item = Output> + '_ {
+        (0..self.row_count()).map(|row| self.output(row))
+    }
+
+    /// The rows whose result is true, in increasing order.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.rows_where(|word, _| word)
+    }
+
+    /// The rows whose result is false, in increasing order.
+    pub fn zeros(&self) -> impl Iterator<Item = usize> + '_ {
+        self.rows_where(|word, dont_care| !word & !dont_care)
+    }
+
+    /// The rows whose result is a don't care, in increasing order.
+    pub fn dont_cares(&self) -> impl Iterator<Item = usize> + '_ {
+        self.rows_where(|_, dont_care| dont_care)
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn has_dont_cares(&self) -> bool {
+        self.dont_cares.iter().any(|w| *w != 0)
+    }
+
+    /// The disjunction of the minterms of the true rows.
+    pub fn canonical_dnf(&self) -> Formula {
+        if self.variables.is_empty() {
+            return Formula::Const(self.get(0));
+        }
+        Formula::disjunction(
+            self.ones()
+                .map(|row| Implicant::minterm(row).to_term(&self.variables)),
+        )
+    }
+
+    /// The conjunction of the maxterms of the false rows.
+    pub fn canonical_cnf(&self) -> Formula {
+        if self.variables.is_empty() {
+            return Formula::Const(self.get(0));
+        }
+        Formula::conjunction(
+            self.zeros()
+                .map(|row| Implicant::minterm(row).to_clause(&self.variables)),
+        )
+    }
+
+    /// The true rows in sum of minterms notation, e.g. `Σm(1,3,5) + d(6,7)`.
+    pub fn minterm_list(&self) -> String {
+        self.index_list("Σm", self.ones(), " + ")
+    }
+
+    /// The false rows in product of maxterms notation, e.g. `ΠM(0,2) · d(6,7)`.
+    pub fn maxterm_list(&self) -> String {
+        self.index_list("ΠM", self.zeros(), " · ")
+    }
+
+    fn index_list(&self, name: &str, rows: impl Iterator<Item = usize>, join: &str) -> String {
+        let list = |rows: Vec<usize>| {
+            rows.iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let mut result = format!("{}({})", name, list(rows.collect()));
+        if self.has_dont_cares() {
+            result += &format!("{}d({})", join, list(self.dont_cares().collect()));
         }
+        result
+    }
+
+    /// Builds a table from a list of minterms such as `Σm(1,3,5)` or of
+    /// maxterms such as `ΠM(0,2)`, optionally followed by don't cares as in
+    /// `Σm(1,3) + d(5)` or `ΠM(0,2) · d(7)`.
+    ///
+    /// `m(..)`, `M(..)` and `*` are accepted in place of `Σm(..)`, `ΠM(..)` and `·`.
+    pub fn from_index_list(variables: Vec<String>, list: &str) -> Result<Self> {
+        let list = list
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>();
+        let (minterms, rest) = if let Some(rest) = strip_any(&list, &["Σm", "m"]) {
+            (true, rest)
+        } else if let Some(rest) = strip_any(&list, &["ΠM", "M"]) {
+            (false, rest)
+        } else {
+            bail!("Expected 'Σm(' or 'ΠM(' at the start of '{}'", list);
+        };
+        let (rows, rest) = parse_indices(rest)?;
+        let dont_cares = match rest {
+            "" => Vec::new(),
+            _ => match strip_any(rest, &["+d", "·d", "*d"]).map(parse_indices) {
+                Some(Ok((dont_cares, ""))) => dont_cares,
+                Some(Err(error)) => return Err(error),
+                _ => bail!("Expected '+ d(' or '· d(' at '{}'", rest),
+            },
+        };
+        let mut table = TruthTable::checked(variables)?;
+        for &row in rows.iter().chain(&dont_cares) {
+            table.check_row(row)?;
+        }
+        if let Some(row) = rows.iter().find(|r| dont_cares.contains(r)) {
+            bail!("Index {} is also a don't care", row);
+        }
+        if !minterms {
+            table.words.fill(!0);
+            table.clear_padding();
+        }
+        for row in rows {
+            table.set(row, minterms);
+        }
+        for row in dont_cares {
+            table.set_output(row, Output::DontCare);
+        }
+        Ok(table)
+    }
+
+    // The rows whose bit is set in `select(word, dont_care)` for their words
+    fn rows_where<F>(&self, select: F) -> impl Iterator<Item = usize> + '_
+    where
+        F: Fn(u64, u64) -> u64 + 'static,
+    {
+        let rows = self.row_count();
+        self.words.iter().enumerate().flat_map(move |(w, &word)| {
+            let mut word = select(word, self.dont_care_word(w));
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(w * WORD_BITS + bit)
+            })
+            .filter(move |row| *row < rows)
+        })
     }
 }
 
@@ -21,17 +416,21 @@ impl fmt::Display for TruthTable {
         for v in &self.variables {
             write!(f, "| {} ", v)?;
         }
-        write!(f, "| = |\n")?;
-        write!(f, "{}|\n", "|---".repeat(len + 1))?;
-        for (i, result) in self.results.iter().enumerate() {
-            for b in 0..len {
+        writeln!(f, "| = |")?;
+        for v in &self.variables {
+            write!(f, "|-{}-", "-".repeat(v.chars().count()))?;
+        }
+        writeln!(f, "|---|")?;
+        for (i, output) in self.outputs().enumerate() {
+            for (b, v) in self.variables.iter().enumerate() {
                 write!(
                     f,
-                    "| {} ",
-                    if i & (1 << (len - 1 - b)) == 0 { 0 } else { 1 }
+                    "| {:<w$} ",
+                    if i & (1 << (len - 1 - b)) == 0 { 0 } else { 1 },
+                    w = v.chars().count()
                 )?
             }
-            write!(f, "| {} |\n", if *result { 1 } else { 0 })?;
+            writeln!(f, "| {} |", output)?;
         }
         write!(f, "")
     }
@@ -42,3 +441,30 @@ impl fmt::Debug for TruthTable {
         fmt::Display::fmt(self, f)
     }
 }
+
+fn strip_any<'a>(s: &'a str, prefixes: &[&str]) -> Option<&'a str> {
+    prefixes.iter().find_map(|prefix| s.strip_prefix(prefix))
+}
+
+// Parses a parenthesised list of indices such as `(1,3,5)`, returning the
+// indices and what follows the closing parenthesis
+fn parse_indices(s: &str) -> Result<(Vec<usize>, &str)> {
+    let inner = s
+        .strip_prefix('(')
+        .with_context(|| format!("Expected '(' at '{}'", s))?;
+    let end = inner
+        .find(')')
+        .with_context(|| format!("Missing ')' in '{}'", s))?;
+    let indices = match &inner[..end] {
+        "" => Vec::new(),
+        list => list
+            .split(',')
+            .map(|index| {
+                index
+                    .parse::<usize>()
+                    .with_context(|| format!("Invalid index '{}'", index))
+            })
+            .collect::<Result<Vec<usize>>>()?,
+    };
+    Ok((indices, &inner[end + 1..]))
+}
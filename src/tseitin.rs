@@ -0,0 +1,428 @@
+use crate::cnf::{Clause, Cnf, Lit};
+use crate::formula::{Formula, Operator};
+use std::collections::HashMap;
+
+/// Which definitions of the auxiliary variables end up in the CNF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// Tseitin: every auxiliary variable is equivalent to its subformula.
+    #[default]
+    Full,
+    /// Plaisted–Greenbaum: an auxiliary variable only implies its subformula
+    /// (or is implied by it) in the polarity the subformula occurs in, which
+    /// roughly halves the number of clauses.
+    Polarity,
+}
+
+/// The definition of an auxiliary variable: the connective applied to the
+/// literals standing for its two operands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Gate {
+    pub op: Operator,
+    pub lhs: Lit,
+    pub rhs: Lit,
+}
+
+/// An equisatisfiable CNF of a formula, with the auxiliary variables it
+/// introduces for the subformulas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TseitinCnf {
+    pub cnf: Cnf,
+    /// The auxiliary variables, in order of creation, which are the last
+    /// variables of the CNF, with the gate each one stands for.
+    pub auxiliaries: Vec<(String, Gate)>,
+}
+
+impl TseitinCnf {
+    /// The subformula the auxiliary variable `name` stands for.
+    pub fn subformula(&self, name: &str) -> Option<Formula> {
+        let first = self.cnf.variables.len() - self.auxiliaries.len();
+        let i = self.auxiliaries.iter().position(|(aux, _)| aux == name)?;
+        Some(self.formula_of(Lit::positive(first + i)))
+    }
+
+    fn formula_of(&self, lit: Lit) -> Formula {
+        let first = self.cnf.variables.len() - self.auxiliaries.len();
+        let formula = match lit.var().checked_sub(first) {
+            None => Formula::var(&self.cnf.variables[lit.var()]),
+            Some(i) => {
+                let gate = self.auxiliaries[i].1;
+                gate.op
+                    .apply(self.formula_of(gate.lhs), self.formula_of(gate.rhs))
+            }
+        };
+        match lit.is_positive() {
+            true => formula,
+            false => !formula,
+        }
+    }
+}
+
+// A node of a formula, with its children by id, so that equal subformulas
+// get the same id without hashing the subformulas themselves
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Node<'a> {
+    Var(&'a str),
+    Not(usize),
+    Binary(Operator, usize, usize),
+    // The inclusive range of true operands accepted, and the operands
+    Cardinality(usize, usize, Vec<usize>),
+}
+
+struct Encoder<'a> {
+    encoding: Encoding,
+    prefix: String,
+    cnf: Cnf,
+    // The index of each variable of the formula in the CNF
+    indices: HashMap<String, usize>,
+    auxiliaries: Vec<(String, Gate)>,
+    // The nodes of the formula by id, children before their parents
+    nodes: Vec<Node<'a>>,
+    ids: HashMap<Node<'a>, usize>,
+    // The literal of each gate of the counters, and whether its positive and
+    // negative definitions were emitted
+    gates: HashMap<Gate, (Lit, bool, bool)>,
+}
+
+impl<'a> Encoder<'a> {
+    fn clause(&mut self, lits: Vec<Lit>) {
+        self.cnf.push(Clause::new(lits));
+    }
+
+    fn intern(&mut self, node: Node<'a>) -> usize {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        self.ids.insert(node.clone(), self.nodes.len());
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    // Gives an id to every node of `formula`, children first, with Nand and
+    // Nor being the negations of an And and an Or
+    fn number(&mut self, formula: &'a Formula) -> usize {
+        formula.fold(|node, ids: Vec<usize>| {
+            let node = match (node, node.as_binary(), node.cardinality_bounds()) {
+                (Formula::Var(name), _, _) => Node::Var(name),
+                (Formula::Not(_), _, _) => Node::Not(ids[0]),
+                (Formula::Nand(..) | Formula::Nor(..), _, _) => {
+                    let op = match node {
+                        Formula::Nand(..) => Operator::And,
+                        _ => Operator::Or,
+                    };
+                    Node::Not(self.intern(Node::Binary(op, ids[0], ids[1])))
+                }
+                (_, Some((op, _, _)), _) => Node::Binary(op, ids[0], ids[1]),
+                (_, _, Some((min, max))) => Node::Cardinality(min, max, ids),
+                _ => unreachable!("constants are folded before encoding"),
+            };
+            self.intern(node)
+        })
+    }
+
+    // Whether each node occurs positively and/or negatively, from the root
+    // down to the leaves
+    fn polarities(&self, root: usize) -> Vec<(bool, bool)> {
+        let mut polarities = vec![(false, false); self.nodes.len()];
+        polarities[root] = match self.encoding {
+            Encoding::Full => (true, true),
+            Encoding::Polarity => (true, false),
+        };
+        for id in (0..self.nodes.len()).rev() {
+            let (positive, negative) = polarities[id];
+            let mut mark = |child: usize, p: bool, n: bool| {
+                let (q, m) = polarities[child];
+                polarities[child] = (p || q, n || m);
+            };
+            match &self.nodes[id] {
+                Node::Var(_) => (),
+                Node::Not(child) => mark(*child, negative, positive),
+                Node::Binary(op, lhs, rhs) => {
+                    let both = positive || negative;
+                    let (l, r) = match op {
+                        // Both polarities of the operands of Xor and Equiv matter
+                        Operator::Xor | Operator::Equiv => ((both, both), (both, both)),
+                        Operator::Imply => ((negative, positive), (positive, negative)),
+                        Operator::Converse => ((positive, negative), (negative, positive)),
+                        _ => ((positive, negative), (positive, negative)),
+                    };
+                    mark(*lhs, l.0, l.1);
+                    mark(*rhs, r.0, r.1);
+                }
+                Node::Cardinality(min, max, operands) => {
+                    // At least `min` operands, which is monotone, and not at
+                    // least `max + 1`, which is antitone
+                    let (lower, upper) = (*min > 0, *max < operands.len());
+                    let p = (positive && lower) || (negative && upper);
+                    let n = (negative && lower) || (positive && upper);
+                    for &operand in operands {
+                        mark(operand, p, n);
+                    }
+                }
+            }
+        }
+        polarities
+    }
+
+    // Encodes every node, children first, and returns the literal of `root`
+    fn encode(&mut self, root: usize) -> Lit {
+        let polarities = self.polarities(root);
+        // The auxiliary variables of the binary nodes are numbered from the
+        // root down, and their gates are filled in below
+        let mut literals = vec![None; self.nodes.len()];
+        for id in (0..self.nodes.len()).rev() {
+            if let (Node::Binary(op, _, _), (true, _) | (_, true)) =
+                (&self.nodes[id], polarities[id])
+            {
+                literals[id] = Some(self.auxiliary(*op));
+            }
+        }
+        for id in 0..self.nodes.len() {
+            let (positive, negative) = polarities[id];
+            if !positive && !negative {
+                continue;
+            }
+            let literal = match &self.nodes[id] {
+                Node::Var(name) => Lit::positive(self.indices[*name]),
+                Node::Not(child) => !literals[*child].unwrap(),
+                Node::Binary(op, lhs, rhs) => {
+                    let (op, literal) = (*op, literals[id].unwrap());
+                    let (a, b) = (literals[*lhs].unwrap(), literals[*rhs].unwrap());
+                    self.set_gate(literal, Gate { op, lhs: a, rhs: b });
+                    // Implications are disjunctions with a negated operand
+                    match op {
+                        Operator::Imply => {
+                            self.define(Operator::Or, literal, !a, b, positive, negative)
+                        }
+                        Operator::Converse => {
+                            self.define(Operator::Or, literal, a, !b, positive, negative)
+                        }
+                        op => self.define(op, literal, a, b, positive, negative),
+                    }
+                    literal
+                }
+                Node::Cardinality(min, max, operands) => {
+                    let (min, max) = (*min, *max);
+                    let operands = operands
+                        .iter()
+                        .map(|&operand| literals[operand].unwrap())
+                        .collect::<Vec<Lit>>();
+                    self.cardinality(min, max, &operands, positive, negative)
+                }
+            };
+            literals[id] = Some(literal);
+        }
+        literals[root].unwrap()
+    }
+
+    // A new auxiliary variable standing for a gate of `op`
+    fn auxiliary(&mut self, op: Operator) -> Lit {
+        let name = format!("{}{}", self.prefix, self.auxiliaries.len());
+        self.cnf.variables.push(name.clone());
+        let literal = Lit::positive(self.cnf.variables.len() - 1);
+        let gate = Gate {
+            op,
+            lhs: literal,
+            rhs: literal,
+        };
+        self.auxiliaries.push((name, gate));
+        literal
+    }
+
+    fn set_gate(&mut self, literal: Lit, gate: Gate) {
+        let first = self.cnf.variables.len() - self.auxiliaries.len();
+        self.auxiliaries[literal.var() - first].1 = gate;
+    }
+
+    // The literal of an And or Or gate of the counters, shared between all
+    // the counters that need it
+    fn gate(&mut self, gate: Gate, positive: bool, negative: bool) -> Lit {
+        let (literal, done_positive, done_negative) = match self.gates.get(&gate) {
+            Some(&entry) => entry,
+            None => {
+                let literal = self.auxiliary(gate.op);
+                self.set_gate(literal, gate);
+                (literal, false, false)
+            }
+        };
+        let (positive, negative) = (positive && !done_positive, negative && !done_negative);
+        self.gates.insert(
+            gate,
+            (
+                literal,
+                done_positive || positive,
+                done_negative || negative,
+            ),
+        );
+        self.define(gate.op, literal, gate.lhs, gate.rhs, positive, negative);
+        literal
+    }
+
+    // A literal that is true when between `min` and `max` of the operands
+    // are, with `0 < min` or `max < operands.len()`
+    fn cardinality(
+        &mut self,
+        min: usize,
+        max: usize,
+        operands: &[Lit],
+        positive: bool,
+        negative: bool,
+    ) -> Lit {
+        let lower = (min > 0).then(|| self.at_least(min, operands, positive, negative));
+        let upper =
+            (max < operands.len()).then(|| !self.at_least(max + 1, operands, negative, positive));
+        match (lower, upper) {
+            (Some(lhs), Some(rhs)) => {
+                let gate = Gate {
+                    op: Operator::And,
+                    lhs,
+                    rhs,
+                };
+                self.gate(gate, positive, negative)
+            }
+            (Some(literal), None) | (None, Some(literal)) => literal,
+            (None, None) => unreachable!("trivial bounds are folded before encoding"),
+        }
+    }
+
+    // A literal that is true when at least `k` of the operands are, for
+    // `0 < k <= operands.len()`, from a sequential counter. After the first
+    // `i` operands, the cell for `j` stands for at least `j` of them, which
+    // is the cell for `j` before the operand or the operand and the cell for
+    // `j - 1`. Only the counts from which `k` can still be reached are kept,
+    // so the counter has at most 2·(n - k + 1)·k gates.
+    fn at_least(&mut self, k: usize, operands: &[Lit], positive: bool, negative: bool) -> Lit {
+        let n = operands.len();
+        // The cells for the counts `low`, `low + 1`, ..., up to min(i, k)
+        let (mut cells, mut low) = (Vec::new(), 1);
+        for (i, &operand) in operands.iter().enumerate() {
+            let next_low = (k + i + 1).saturating_sub(n).max(1);
+            let mut next = Vec::new();
+            for j in next_low..=k.min(i + 1) {
+                let carry = match j - 1 {
+                    0 => operand,
+                    count => {
+                        let gate = Gate {
+                            op: Operator::And,
+                            lhs: cells[count - low],
+                            rhs: operand,
+                        };
+                        self.gate(gate, positive, negative)
+                    }
+                };
+                next.push(match j <= i {
+                    true => {
+                        let gate = Gate {
+                            op: Operator::Or,
+                            lhs: cells[j - low],
+                            rhs: carry,
+                        };
+                        self.gate(gate, positive, negative)
+                    }
+                    false => carry,
+                });
+            }
+            (cells, low) = (next, next_low);
+        }
+        cells[0]
+    }
+
+    // Emits `t -> a op b` when `positive` and `a op b -> t` when `negative`
+    fn define(&mut self, op: Operator, t: Lit, a: Lit, b: Lit, positive: bool, negative: bool) {
+        let (nt, na, nb) = (!t, !a, !b);
+        match op {
+            Operator::And => {
+                if positive {
+                    self.clause(vec![nt, a]);
+                    self.clause(vec![nt, b]);
+                }
+                if negative {
+                    self.clause(vec![t, na, nb]);
+                }
+            }
+            Operator::Or => {
+                if positive {
+                    self.clause(vec![nt, a, b]);
+                }
+                if negative {
+                    self.clause(vec![t, na]);
+                    self.clause(vec![t, nb]);
+                }
+            }
+            Operator::Xor => {
+                if positive {
+                    self.clause(vec![nt, a, b]);
+                    self.clause(vec![nt, na, nb]);
+                }
+                if negative {
+                    self.clause(vec![t, na, b]);
+                    self.clause(vec![t, a, nb]);
+                }
+            }
+            Operator::Equiv => {
+                if positive {
+                    self.clause(vec![nt, na, b]);
+                    self.clause(vec![nt, a, nb]);
+                }
+                if negative {
+                    self.clause(vec![t, a, b]);
+                    self.clause(vec![t, na, nb]);
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// `prefix` with as many more leading underscores as needed for no variable
+/// to start with it, so that the names of new variables made from it are
+/// fresh.
+pub(crate) fn unique_prefix(variables: &[String], prefix: &str) -> String {
+    let mut prefix = String::from(prefix);
+    while variables.iter().any(|v| v.starts_with(&prefix)) {
+        prefix.insert(0, '_');
+    }
+    prefix
+}
+
+impl Formula {
+    /// An equisatisfiable CNF of the formula whose size is linear in the
+    /// size of the formula, with an auxiliary variable for each distinct
+    /// binary subformula.
+    ///
+    /// The auxiliary variables are named `_t0`, `_t1`, ... (with more leading
+    /// underscores if the formula already has variables named that way).
+    /// The n-ary connectives are encoded by sequential counters made of And
+    /// and Or gates, with O(n·k) auxiliary variables for `k` of `n` operands.
+    pub fn tseitin(&self, encoding: Encoding) -> TseitinCnf {
+        let formula = self.fold_constants();
+        let variables = formula.variables();
+        let prefix = unique_prefix(&variables, "_t");
+        let mut encoder = Encoder {
+            encoding,
+            prefix,
+            indices: variables
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.clone(), i))
+                .collect(),
+            cnf: Cnf::new(variables),
+            auxiliaries: Vec::new(),
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            gates: HashMap::new(),
+        };
+        match formula {
+            Formula::Const(value) => encoder.cnf = Cnf::constant(value),
+            _ => {
+                let root = encoder.number(&formula);
+                let root = encoder.encode(root);
+                encoder.clause(vec![root]);
+            }
+        }
+        TseitinCnf {
+            cnf: encoder.cnf,
+            auxiliaries: encoder.auxiliaries,
+        }
+    }
+}